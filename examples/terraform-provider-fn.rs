@@ -17,10 +17,12 @@
 use anyhow::Result;
 use async_trait::async_trait;
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 use tf_provider::schema::{Block, Description, FunctionSchema, Parameter, Schema, Type};
-use tf_provider::value::ValueEmpty;
-use tf_provider::{map, serve, Diagnostics, Function, Provider};
+use tf_provider::value::{ListMerge, ValueAny, ValueEmpty};
+use tf_provider::{map, serve, Diagnostics, Function, FunctionError, Provider};
 
 #[derive(Debug, Default, Clone, Copy)]
 pub struct Add;
@@ -63,8 +65,105 @@ impl Function for Add {
         &self,
         _diags: &mut Diagnostics,
         params: Self::Input<'a>,
-    ) -> Option<Self::Output<'a>> {
-        Some(params.a + params.b)
+    ) -> Result<Self::Output<'a>, FunctionError> {
+        params
+            .a
+            .checked_add(params.b)
+            .ok_or(FunctionError::general("Integer overflow"))
+    }
+}
+
+/// Dynamic value, encoded as a `[type, value]` pair
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Dynamic(String, ValueAny);
+
+impl From<ValueAny> for Dynamic {
+    fn from(value: ValueAny) -> Self {
+        fn type_of(value: &ValueAny) -> Type {
+            match value {
+                ValueAny::String(_) => Type::String,
+                ValueAny::Number(_) => Type::Number,
+                ValueAny::Bool(_) => Type::Bool,
+                ValueAny::List(list) => Type::Tuple(list.iter().map(type_of).collect()),
+                ValueAny::Map(map) => Type::Object(
+                    map.iter()
+                        .map(|(name, value)| (name.clone(), type_of(value)))
+                        .collect::<HashMap<_, _>>(),
+                ),
+                // Dynamic values are encoded along with their own type
+                ValueAny::Dynamic(..) => Type::Any,
+                // Null and unknown values of dynamic type are encoded without their type
+                ValueAny::Null | ValueAny::Unknown => Type::Any,
+            }
+        }
+        Self(type_of(&value).to_string(), value)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DeepMerge;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeepMergeArgs {
+    a: Dynamic,
+    b: Dynamic,
+    mode: String,
+}
+
+#[async_trait]
+impl Function for DeepMerge {
+    type Input<'a> = DeepMergeArgs;
+    type Output<'a> = Dynamic;
+
+    fn schema(&self, _diags: &mut Diagnostics) -> Option<FunctionSchema> {
+        Some(FunctionSchema {
+            parameters: vec![
+                Parameter {
+                    name: "a".into(),
+                    param_type: Type::Any,
+                    description: Description::plain("Base object"),
+                    ..Default::default()
+                },
+                Parameter {
+                    name: "b".into(),
+                    param_type: Type::Any,
+                    description: Description::plain("Object merged on top of `a`"),
+                    ..Default::default()
+                },
+                Parameter {
+                    name: "mode".into(),
+                    param_type: Type::String,
+                    description: Description::plain(
+                        "How lists are merged: either `replace` or `concat`",
+                    ),
+                    ..Default::default()
+                },
+            ],
+            return_type: Type::Any,
+            summary: "deepmerge".into(),
+            description: Description::plain(
+                "Recursively merge two objects, `b` winning on conflicts",
+            ),
+            ..Default::default()
+        })
+    }
+
+    async fn call<'a>(
+        &self,
+        _diags: &mut Diagnostics,
+        params: Self::Input<'a>,
+    ) -> Result<Self::Output<'a>, FunctionError> {
+        let lists = match params.mode.as_str() {
+            "replace" => ListMerge::Replace,
+            "concat" => ListMerge::Concat,
+            mode => {
+                return Err(FunctionError::argument(
+                    2,
+                    format!("Invalid mode `{mode}`: expected `replace` or `concat`"),
+                ))
+            }
+        };
+        Ok(params.a.1.deep_merge(params.b.1, lists).into())
     }
 }
 
@@ -92,6 +191,7 @@ impl Provider for FnProvider {
     ) -> Option<std::collections::HashMap<String, Box<dyn tf_provider::DynamicFunction>>> {
         Some(map! {
             "add" => Add,
+            "deepmerge" => DeepMerge,
         })
     }
 }
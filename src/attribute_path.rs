@@ -141,6 +141,86 @@ impl AttributePath {
         self.steps.append(&mut steps.steps);
         self
     }
+
+    /// Get the path without its last step
+    ///
+    /// Returns [`None`] if the path has no step.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::AttributePath;
+    /// let path = AttributePath::new("inputs").key("foo").attribute("cmd").index(0);
+    /// assert_eq!(path.to_string(), r#"inputs["foo"].cmd[0]"#);
+    ///
+    /// let path = path.parent().unwrap();
+    /// assert_eq!(path.to_string(), r#"inputs["foo"].cmd"#);
+    /// let path = path.parent().unwrap();
+    /// assert_eq!(path.to_string(), r#"inputs["foo"]"#);
+    /// let path = path.parent().unwrap();
+    /// assert_eq!(path.to_string(), "inputs");
+    /// let path = path.parent().unwrap();
+    /// assert_eq!(path, AttributePath::root());
+    /// assert_eq!(path.to_string(), "");
+    /// assert_eq!(path.parent(), None);
+    /// ```
+    pub fn parent(&self) -> Option<AttributePath> {
+        let (_, steps) = self.steps.split_last()?;
+        Some(Self {
+            steps: steps.to_vec(),
+        })
+    }
+
+    /// Get the path without the leading `prefix`
+    ///
+    /// Returns [`None`] if the path does not start with `prefix`.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - Steps to remove from the start of the path
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::AttributePath;
+    /// let path = AttributePath::new("connect").index(0).attribute("host");
+    ///
+    /// let prefix = AttributePath::new("connect").index(0);
+    /// assert_eq!(path.strip_prefix(&prefix), Some(AttributePath::new("host")));
+    /// assert_eq!(path.strip_prefix(&path), Some(AttributePath::root()));
+    /// assert_eq!(path.strip_prefix(&AttributePath::root()), Some(path.clone()));
+    ///
+    /// assert_eq!(path.strip_prefix(&AttributePath::new("connect").index(1)), None);
+    /// assert_eq!(path.strip_prefix(&AttributePath::new("host")), None);
+    /// assert_eq!(prefix.strip_prefix(&path), None);
+    /// ```
+    pub fn strip_prefix(&self, prefix: &AttributePath) -> Option<AttributePath> {
+        let steps = self.steps.strip_prefix(prefix.steps.as_slice())?;
+        Some(Self {
+            steps: steps.to_vec(),
+        })
+    }
+
+    /// Get the path relative to `base`
+    ///
+    /// If the path is not within `base`, the whole path is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - Path the result is relative to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::AttributePath;
+    /// let path = AttributePath::new("connect").index(0).attribute("host");
+    ///
+    /// assert_eq!(path.relative_to(&AttributePath::new("connect").index(0)).to_string(), "host");
+    /// assert_eq!(path.relative_to(&AttributePath::new("command")).to_string(), "connect[0].host");
+    /// ```
+    pub fn relative_to(&self, base: &AttributePath) -> AttributePath {
+        self.strip_prefix(base).unwrap_or_else(|| self.clone())
+    }
 }
 
 impl Display for AttributePath {
@@ -19,17 +19,49 @@
 use std::{
     backtrace::{Backtrace, BacktraceStatus},
     borrow::Cow,
+    collections::HashSet,
+    hash::{Hash, Hasher},
 };
 
-use crate::{attribute_path::AttributePath, tfplugin6, utils::CollectDiagnostics};
+use crate::{
+    attribute_path::AttributePath, function::FunctionError, tfplugin6, utils::CollectDiagnostics,
+};
 
 /// List of Errors and Warnings to send back to Terraform
-#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+///
+/// Equality and hashing only depend on the errors and the warnings, not on how they are interleaved.
+#[derive(Clone, Debug, Default)]
 pub struct Diagnostics {
     /// List of errors
     pub errors: Vec<Diagnostic>,
     /// List of warnings
     pub warnings: Vec<Diagnostic>,
+    /// Severities of the diagnostics added with [`Diagnostics::add_error`] and [`Diagnostics::add_warning`], in insertion order
+    order: Vec<Severity>,
+}
+
+impl PartialEq for Diagnostics {
+    fn eq(&self, other: &Self) -> bool {
+        self.errors == other.errors && self.warnings == other.warnings
+    }
+}
+
+impl Eq for Diagnostics {}
+
+impl Hash for Diagnostics {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.errors.hash(state);
+        self.warnings.hash(state);
+    }
+}
+
+/// Severity of a [`Diagnostic`]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Severity {
+    /// The diagnostic is an error
+    Error,
+    /// The diagnostic is a warning
+    Warning,
 }
 
 impl Diagnostics {
@@ -39,7 +71,8 @@ impl Diagnostics {
     ///
     /// * `diag` - diagnostic
     pub fn add_error(&mut self, diag: Diagnostic) {
-        self.errors.push(diag)
+        self.order.push(Severity::Error);
+        self.errors.push(diag);
     }
 
     /// Add a warning diagnostic
@@ -48,7 +81,8 @@ impl Diagnostics {
     ///
     /// * `diag` - diagnostic
     pub fn add_warning(&mut self, diag: Diagnostic) {
-        self.warnings.push(diag)
+        self.order.push(Severity::Warning);
+        self.warnings.push(diag);
     }
 
     /// Add an error
@@ -67,6 +101,62 @@ impl Diagnostics {
         self.add_error(Diagnostic::new(summary, detail, attribute))
     }
 
+    /// Add an error spanning multiple attributes
+    ///
+    /// One error is added for each attribute, all sharing the same summary and detail,
+    /// so that Terraform reports the error on every attribute.
+    /// If `attributes` is empty, a single error without [`AttributePath`] is added.
+    ///
+    /// # Arguments
+    ///
+    /// * `summary` - Summary of the diagnostic component
+    /// * `detail` - Detail of the diagnostic component
+    /// * `attributes` - Attribute paths for the diagnostic component
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::{AttributePath, Diagnostics};
+    /// fn check_content(diags: &mut Diagnostics, defined: &[&'static str]) {
+    ///     let sources = ["content", "content_base64", "content_source"];
+    ///     if defined.len() != 1 {
+    ///         diags.error_multi(
+    ///             "Invalid content",
+    ///             format!("Exactly one of {} must be set", sources.join(", ")),
+    ///             defined.iter().copied().map(AttributePath::new).collect(),
+    ///         );
+    ///     }
+    /// }
+    ///
+    /// let mut diags = Diagnostics::default();
+    /// check_content(&mut diags, &["content", "content_source"]);
+    /// assert_eq!(diags.error_count(), 2);
+    /// assert_eq!(diags.errors[0].attribute, AttributePath::new("content"));
+    /// assert_eq!(diags.errors[1].attribute, AttributePath::new("content_source"));
+    /// assert!(diags.errors.iter().all(|diag| diag.summary == "Invalid content"));
+    ///
+    /// // None of the content attributes is set
+    /// let mut diags = Diagnostics::default();
+    /// check_content(&mut diags, &[]);
+    /// assert_eq!(diags.error_count(), 1);
+    /// assert_eq!(diags.errors[0].attribute, AttributePath::root());
+    /// assert_eq!(diags.errors[0].summary, "Invalid content");
+    /// ```
+    pub fn error_multi<S: Into<Cow<'static, str>>, D: Into<Cow<'static, str>>>(
+        &mut self,
+        summary: S,
+        detail: D,
+        attributes: Vec<AttributePath>,
+    ) {
+        if attributes.is_empty() {
+            return self.root_error(summary, detail);
+        }
+        let (summary, detail) = (summary.into(), detail.into());
+        for attribute in attributes {
+            self.error(summary.clone(), detail.clone(), attribute);
+        }
+    }
+
     /// Add an error without [`AttributePath`]
     ///
     /// # Arguments
@@ -158,9 +248,179 @@ impl Diagnostics {
     }
 
     /// Append other diagnostics
-    pub fn add_diagnostics(&mut self, mut diags: Diagnostics) {
-        self.errors.append(&mut diags.errors);
-        self.warnings.append(&mut diags.warnings);
+    ///
+    /// Errors and warnings of `diags` are appended in their insertion order,
+    /// with their attribute paths preserved.
+    ///
+    /// # Arguments
+    ///
+    /// * `diags` - diagnostics to append
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::{AttributePath, Diagnostics};
+    /// let mut host = Diagnostics::default();
+    /// host.error_short("invalid host", AttributePath::new("connect").attribute("host"));
+    /// let mut port = Diagnostics::default();
+    /// port.warning_short("unusual port", AttributePath::new("connect").attribute("port"));
+    ///
+    /// let mut diags = Diagnostics::default();
+    /// assert!(!diags.has_errors());
+    /// diags.add_diagnostics(host);
+    /// diags.add_diagnostics(port);
+    ///
+    /// assert!(diags.has_errors());
+    /// assert_eq!(diags.error_count(), 1);
+    /// assert_eq!(diags.warning_count(), 1);
+    /// assert_eq!(diags.errors[0].attribute.to_string(), "connect.host");
+    /// assert_eq!(diags.warnings[0].attribute.to_string(), "connect.port");
+    /// ```
+    pub fn add_diagnostics(&mut self, diags: Diagnostics) {
+        for (severity, diag) in diags.into_ordered() {
+            match severity {
+                Severity::Error => self.add_error(diag),
+                Severity::Warning => self.add_warning(diag),
+            }
+        }
+    }
+
+    /// Run `f` and prepend a context to the details of the errors it records
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Context of the operation, prepended to the error details
+    /// * `f` - Operation that records diagnostics
+    ///
+    /// # Remarks
+    ///
+    /// Warnings recorded by `f` are kept as-is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::{AttributePath, Diagnostics};
+    /// let mut diags = Diagnostics::default();
+    /// let content = diags.with_context("while reading remote file /x for resource foo", |diags| {
+    ///     diags.error("Read failed", "No such file", AttributePath::new("path"));
+    ///     diags.warning("Slow connection", "High latency", AttributePath::new("connect"));
+    ///     Option::<Vec<u8>>::None
+    /// });
+    ///
+    /// assert_eq!(content, None);
+    /// assert!(diags.errors[0].detail.starts_with("while reading remote file /x for resource foo: No such file"));
+    /// assert_eq!(diags.errors[0].attribute, AttributePath::new("path"));
+    /// assert!(diags.warnings[0].detail.starts_with("High latency"));
+    /// ```
+    pub fn with_context<R>(&mut self, context: &str, f: impl FnOnce(&mut Diagnostics) -> R) -> R {
+        let mut diags = Diagnostics::default();
+        let result = f(&mut diags);
+        for error in &mut diags.errors {
+            error.detail = if error.detail.is_empty() {
+                context.to_owned().into()
+            } else {
+                format!("{context}: {}", error.detail).into()
+            };
+        }
+        self.add_diagnostics(diags);
+        result
+    }
+
+    /// Check if there is any error
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// Get the number of errors
+    pub fn error_count(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Get the number of warnings
+    pub fn warning_count(&self) -> usize {
+        self.warnings.len()
+    }
+
+    /// Iterate over all the diagnostics, errors and warnings interleaved in insertion order
+    ///
+    /// # Remarks
+    ///
+    /// Diagnostics pushed directly into [`Diagnostics::errors`] or [`Diagnostics::warnings`]
+    /// come after the ones added with [`Diagnostics::add_error`] and [`Diagnostics::add_warning`],
+    /// errors first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::{Diagnostic, Diagnostics, Severity};
+    /// let mut diags = Diagnostics::default();
+    /// diags.root_warning_short("first");
+    /// diags.root_error_short("second");
+    /// diags.root_warning_short("third");
+    ///
+    /// let order = diags
+    ///     .iter()
+    ///     .map(|(severity, diag)| (severity, diag.summary.as_ref()))
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(order, [
+    ///     (Severity::Warning, "first"),
+    ///     (Severity::Error, "second"),
+    ///     (Severity::Warning, "third"),
+    /// ]);
+    ///
+    /// // Direct pushes come last, and the order does not affect equality
+    /// let mut pushed = Diagnostics::default();
+    /// pushed.root_warning_short("first");
+    /// pushed.warnings.push(Diagnostic::root_short("pushed"));
+    /// pushed.root_error_short("second");
+    /// assert!(pushed.iter().map(|(_, diag)| diag.summary.as_ref()).eq(["first", "second", "pushed"]));
+    ///
+    /// let mut reordered = Diagnostics::default();
+    /// reordered.add_error(diags.errors[0].clone());
+    /// reordered.add_warning(diags.warnings[0].clone());
+    /// reordered.add_warning(diags.warnings[1].clone());
+    /// assert_eq!(reordered, diags);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (Severity, &Diagnostic)> {
+        interleave(&self.order, &self.errors, &self.warnings).into_iter()
+    }
+
+    fn into_ordered(self) -> Vec<(Severity, Diagnostic)> {
+        interleave(&self.order, self.errors, self.warnings)
+    }
+
+    /// Remove duplicated diagnostics
+    ///
+    /// Diagnostics are duplicates if they have the same severity, summary, detail, and attribute path.
+    /// Only the first occurrence of each diagnostic is kept, and the order is preserved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::{AttributePath, Diagnostics};
+    /// let mut diags = Diagnostics::default();
+    /// for _ in 0..3 {
+    ///     diags.root_error_short("connection failed");
+    ///     diags.warning_short("connection failed", AttributePath::new("host"));
+    /// }
+    /// diags.error_short("connection failed", AttributePath::new("host"));
+    /// diags.dedup();
+    ///
+    /// assert_eq!(diags.errors.len(), 2);
+    /// assert_eq!(diags.warnings.len(), 1);
+    /// ```
+    pub fn dedup(&mut self) {
+        let mut seen = HashSet::new();
+        let diags = std::mem::take(self)
+            .into_ordered()
+            .into_iter()
+            .filter(|diag| seen.insert(diag.clone()));
+        for (severity, diag) in diags {
+            match severity {
+                Severity::Error => self.add_error(diag),
+                Severity::Warning => self.add_warning(diag),
+            }
+        }
     }
 
     /// Add an internal error if there is no existing errors
@@ -177,6 +437,49 @@ impl Diagnostics {
     pub fn function_error<S: Into<Cow<'static, str>>>(&mut self, index: i64, message: S) {
         self.add_error(Diagnostic::function(index, message))
     }
+
+    /// Collect the result of a function call
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - result of the function call
+    ///
+    /// # Remarks
+    ///
+    /// Returns [`None`] iff there is an error reported in diagnostics
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::{AttributePath, Diagnostics, FunctionError};
+    /// fn divide(a: i64, b: i64) -> Result<i64, FunctionError> {
+    ///     if b == 0 {
+    ///         return Err(FunctionError::argument(1, "Division by zero"));
+    ///     }
+    ///     a.checked_div(b).ok_or(FunctionError::general("Overflow"))
+    /// }
+    ///
+    /// let mut diags = Diagnostics::default();
+    /// assert_eq!(diags.function_result(divide(6, 3)), Some(2));
+    /// assert!(diags.errors.is_empty());
+    ///
+    /// assert_eq!(diags.function_result(divide(6, 0)), None);
+    /// assert_eq!(diags.errors[0].summary, "Division by zero");
+    /// assert_eq!(diags.errors[0].attribute, AttributePath::function_argument(1));
+    ///
+    /// assert_eq!(diags.function_result(divide(i64::MIN, -1)), None);
+    /// assert_eq!(diags.errors[1].summary, "Overflow");
+    /// assert_eq!(diags.errors[1].attribute, AttributePath::root());
+    /// ```
+    pub fn function_result<T>(&mut self, result: Result<T, FunctionError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.add_error(err.into());
+                None
+            }
+        }
+    }
 }
 
 /// Diagnostic component
@@ -266,12 +569,41 @@ impl Diagnostic {
     }
 }
 
+/// Interleave errors and warnings according to `order`
+///
+/// Diagnostics that are not accounted for in `order` come last, errors first.
+fn interleave<T>(
+    order: &[Severity],
+    errors: impl IntoIterator<Item = T>,
+    warnings: impl IntoIterator<Item = T>,
+) -> Vec<(Severity, T)> {
+    let (mut errors, mut warnings) = (errors.into_iter(), warnings.into_iter());
+    let mut diags = order
+        .iter()
+        .filter_map(|&severity| {
+            let diag = match severity {
+                Severity::Error => errors.next(),
+                Severity::Warning => warnings.next(),
+            };
+            Some((severity, diag?))
+        })
+        .collect::<Vec<_>>();
+    diags.extend(errors.map(|diag| (Severity::Error, diag)));
+    diags.extend(warnings.map(|diag| (Severity::Warning, diag)));
+    diags
+}
+
 impl From<Diagnostics> for ::prost::alloc::vec::Vec<tfplugin6::Diagnostic> {
     fn from(value: Diagnostics) -> Self {
-        use tfplugin6::diagnostic::Severity;
-        let map_cvt = |vec: Vec<Diagnostic>, severity: Severity| {
-            vec.into_iter().map(move |diag| tfplugin6::Diagnostic {
-                severity: severity.into(),
+        value
+            .into_ordered()
+            .into_iter()
+            .map(|(severity, diag)| tfplugin6::Diagnostic {
+                severity: match severity {
+                    Severity::Error => tfplugin6::diagnostic::Severity::Error,
+                    Severity::Warning => tfplugin6::diagnostic::Severity::Warning,
+                }
+                .into(),
                 summary: diag.summary.into_owned(),
                 detail: diag.detail.into_owned(),
                 attribute: if diag.attribute.steps.is_empty() {
@@ -280,9 +612,6 @@ impl From<Diagnostics> for ::prost::alloc::vec::Vec<tfplugin6::Diagnostic> {
                     Some(diag.attribute.into())
                 },
             })
-        };
-        map_cvt(value.errors, Severity::Error)
-            .chain(map_cvt(value.warnings, Severity::Warning))
             .collect()
     }
 }
@@ -15,10 +15,12 @@
 // limitations under the License.
 
 //! [`Function`] module
+use std::borrow::Cow;
+
 use async_trait::async_trait;
 use serde::{de, Deserialize, Serialize};
 
-use crate::{raw::RawValue, schema::FunctionSchema, Diagnostics};
+use crate::{raw::RawValue, schema::FunctionSchema, Diagnostic, Diagnostics};
 
 #[async_trait]
 /// Trait for implementing a function with automatic serialization/deserialization
@@ -51,18 +53,73 @@ pub trait Function: Send + Sync {
     ///
     /// # Arguments
     ///
-    /// * `diags` - Diagnostics to record warnings and errors that occured when calling the function
+    /// * `diags` - Diagnostics to record warnings that occured when calling the function
     /// * `params` - Function parameters packed into the input type
     ///
     /// # Remarks
     ///
+    /// The error is reported to Terraform, either on the argument that caused it, or on the whole call.
     /// The return is ignored if there is an error in diagnostics.
-    /// If the return is [`None`], an ad-hoc error is added to diagnostics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_trait::async_trait;
+    /// # use serde::Deserialize;
+    /// # use tf_provider::schema::FunctionSchema;
+    /// # use tf_provider::{AttributePath, Diagnostics, DynamicFunction, Function, FunctionError, RawValue};
+    /// struct Divide;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Args {
+    ///     a: i64,
+    ///     b: i64,
+    /// }
+    ///
+    /// #[async_trait]
+    /// impl Function for Divide {
+    ///     type Input<'a> = Args;
+    ///     type Output<'a> = i64;
+    ///
+    ///     fn schema(&self, _diags: &mut Diagnostics) -> Option<FunctionSchema> {
+    ///         Some(FunctionSchema::default())
+    ///     }
+    ///
+    ///     async fn call<'a>(&self, _diags: &mut Diagnostics, params: Args) -> Result<i64, FunctionError> {
+    ///         if params.b == 0 {
+    ///             return Err(FunctionError::argument(1, "Division by zero"));
+    ///         }
+    ///         params.a.checked_div(params.b).ok_or(FunctionError::general("Overflow"))
+    ///     }
+    /// }
+    ///
+    /// let call = |a: i64, b: i64| {
+    ///     let mut diags = Diagnostics::default();
+    ///     let params = vec![
+    ///         RawValue::serialize(&mut diags, &a).unwrap(),
+    ///         RawValue::serialize(&mut diags, &b).unwrap(),
+    ///     ];
+    ///     let result = futures::executor::block_on(DynamicFunction::call(&Divide, &mut diags, params));
+    ///     (result.map(|result| result.deserialize::<i64>(&mut diags).unwrap()), diags)
+    /// };
+    ///
+    /// assert_eq!(call(6, 3).0, Some(2));
+    ///
+    /// let (result, diags) = call(6, 0);
+    /// assert_eq!(result, None);
+    /// assert_eq!(diags.errors[0].summary, "Division by zero");
+    /// assert_eq!(diags.errors[0].attribute, AttributePath::function_argument(1));
+    ///
+    /// let (result, diags) = call(i64::MIN, -1);
+    /// assert_eq!(result, None);
+    /// assert_eq!(diags.errors[0].summary, "Overflow");
+    /// assert_eq!(diags.errors[0].attribute, AttributePath::root());
+    /// ```
     async fn call<'a>(
         &self,
         diags: &mut Diagnostics,
         params: Self::Input<'a>,
-    ) -> Option<Self::Output<'a>>;
+    ) -> Result<Self::Output<'a>, FunctionError>;
 }
 
 #[async_trait]
@@ -110,7 +167,8 @@ impl<T: Function> DynamicFunction for T {
         };
         match Deserialize::deserialize(&mut decoder) {
             Ok(params) => {
-                let value = <T as Function>::call(self, diags, params).await?;
+                let result = <T as Function>::call(self, diags, params).await;
+                let value = diags.function_result(result)?;
                 RawValue::serialize(diags, &value)
             }
             Err(DecoderError::UnsupportedFormat) => {
@@ -139,6 +197,60 @@ impl<T: Function + 'static> From<T> for Box<dyn DynamicFunction> {
     }
 }
 
+/// Error of a function call
+///
+/// # See Also
+///
+/// [`Diagnostics::function_result`]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum FunctionError {
+    /// Error caused by the argument at the given index
+    Argument(i64, Cow<'static, str>),
+    /// Error not related to any argument
+    General(Cow<'static, str>),
+}
+
+impl FunctionError {
+    /// Create an error caused by an argument
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - index of the argument triggering the error
+    /// * `message` - message of the error
+    pub fn argument<S: Into<Cow<'static, str>>>(index: i64, message: S) -> Self {
+        Self::Argument(index, message.into())
+    }
+
+    /// Create an error not related to any argument
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - message of the error
+    pub fn general<S: Into<Cow<'static, str>>>(message: S) -> Self {
+        Self::General(message.into())
+    }
+}
+
+impl std::fmt::Display for FunctionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Argument(index, message) => write!(f, "Argument #{}: {}", index + 1, message),
+            Self::General(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for FunctionError {}
+
+impl From<FunctionError> for Diagnostic {
+    fn from(value: FunctionError) -> Self {
+        match value {
+            FunctionError::Argument(index, message) => Diagnostic::function(index, message),
+            FunctionError::General(message) => Diagnostic::root_short(message),
+        }
+    }
+}
+
 struct Decoder<'de> {
     params: &'de [RawValue],
     index: usize,
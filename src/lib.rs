@@ -25,16 +25,25 @@ mod attribute_path;
 mod data_source;
 mod diagnostics;
 mod function;
+mod metrics;
 mod plugin;
 mod provider;
 mod raw;
 mod resource;
 mod server;
+mod signal;
 mod tf6provider;
+#[cfg(test)]
+mod tfplugin6_client;
+#[cfg(test)]
+mod tfplugin6_compat;
 mod utils;
 
+pub mod plan_modifier;
 pub mod schema;
+pub mod testing;
 pub mod value;
+pub mod value_diff;
 
 mod tfplugin6 {
     tonic::include_proto!("tfplugin6");
@@ -42,11 +51,20 @@ mod tfplugin6 {
 
 pub use attribute_path::{AttributePath, AttributePathStep};
 pub use data_source::{DataSource, DynamicDataSource};
-pub use diagnostics::{Diagnostic, Diagnostics};
-pub use function::{DynamicFunction, Function};
-pub use provider::{DynamicProvider, Provider};
+pub use diagnostics::{Diagnostic, Diagnostics, Severity};
+pub use function::{DynamicFunction, Function, FunctionError};
+pub use provider::{
+    DataSourceRegistry, DynamicProvider, FunctionRegistry, Provider, Registry, ResourceRegistry,
+};
+pub use raw::RawValue;
 pub use resource::{DynamicResource, Resource};
-pub use server::{serve, serve_dynamic};
+pub use server::{
+    build_service, build_service_dynamic, serve, serve_dynamic, serve_dynamic_with_ready,
+    serve_with_ready,
+};
+
+#[cfg(feature = "derive")]
+pub use tf_provider_derive::Normalize;
 
 #[macro_export]
 /// Build a hash map
@@ -0,0 +1,218 @@
+// This file is part of the tf-provider project
+//
+// Copyright (C) ANEO, 2024-2024. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Metrics of the RPCs served by the provider
+//!
+//! With the `metrics` feature, the metrics are exposed in the Prometheus text format
+//! on the address given by the `TF_PROVIDER_METRICS_ADDR` environment variable.
+//! Without the feature, recording the metrics is compiled out.
+
+#[cfg(feature = "metrics")]
+use std::collections::BTreeMap;
+#[cfg(feature = "metrics")]
+use std::sync::Mutex;
+#[cfg(feature = "metrics")]
+use std::time::{Duration, Instant};
+
+/// Metrics of the RPCs served by the provider
+#[derive(Debug, Default)]
+pub(crate) struct Metrics {
+    #[cfg(feature = "metrics")]
+    inner: Mutex<Inner>,
+}
+
+#[cfg(feature = "metrics")]
+#[derive(Debug, Default)]
+struct Inner {
+    rpcs: BTreeMap<&'static str, RpcMetrics>,
+    in_flight: u64,
+}
+
+#[cfg(feature = "metrics")]
+#[derive(Debug, Default, Clone, Copy)]
+struct RpcMetrics {
+    count: u64,
+    duration: Duration,
+}
+
+/// Guard recording an RPC when dropped
+pub(crate) struct RpcTimer<'a> {
+    #[cfg(feature = "metrics")]
+    metrics: &'a Metrics,
+    #[cfg(feature = "metrics")]
+    rpc: &'static str,
+    #[cfg(feature = "metrics")]
+    start: Instant,
+    #[cfg(not(feature = "metrics"))]
+    _metrics: std::marker::PhantomData<&'a Metrics>,
+}
+
+impl Metrics {
+    /// Start recording the RPC `rpc`, until the returned guard is dropped
+    #[cfg(not(feature = "metrics"))]
+    #[inline(always)]
+    pub(crate) fn start(&self, _rpc: &'static str) -> RpcTimer<'_> {
+        RpcTimer {
+            _metrics: std::marker::PhantomData,
+        }
+    }
+
+    /// Start recording the RPC `rpc`, until the returned guard is dropped
+    #[cfg(feature = "metrics")]
+    pub(crate) fn start(&self, rpc: &'static str) -> RpcTimer<'_> {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.in_flight += 1;
+        }
+        RpcTimer {
+            metrics: self,
+            rpc,
+            start: Instant::now(),
+        }
+    }
+    /// Render the metrics in the Prometheus text format
+    #[cfg(feature = "metrics")]
+    pub(crate) fn render(&self) -> String {
+        use std::fmt::Write;
+
+        let Ok(inner) = self.inner.lock() else {
+            return String::new();
+        };
+        let mut text = String::new();
+        _ = writeln!(
+            text,
+            "# HELP tf_provider_rpc_requests_total Number of completed RPCs"
+        );
+        _ = writeln!(text, "# TYPE tf_provider_rpc_requests_total counter");
+        for (rpc, metrics) in &inner.rpcs {
+            _ = writeln!(
+                text,
+                "tf_provider_rpc_requests_total{{rpc=\"{rpc}\"}} {}",
+                metrics.count
+            );
+        }
+        _ = writeln!(
+            text,
+            "# HELP tf_provider_rpc_duration_seconds_total Cumulated duration of the completed RPCs"
+        );
+        _ = writeln!(
+            text,
+            "# TYPE tf_provider_rpc_duration_seconds_total counter"
+        );
+        for (rpc, metrics) in &inner.rpcs {
+            _ = writeln!(
+                text,
+                "tf_provider_rpc_duration_seconds_total{{rpc=\"{rpc}\"}} {}",
+                metrics.duration.as_secs_f64()
+            );
+        }
+        _ = writeln!(
+            text,
+            "# HELP tf_provider_rpc_in_flight Number of RPCs being served"
+        );
+        _ = writeln!(text, "# TYPE tf_provider_rpc_in_flight gauge");
+        _ = writeln!(text, "tf_provider_rpc_in_flight {}", inner.in_flight);
+        text
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl Drop for RpcTimer<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut inner) = self.metrics.inner.lock() {
+            inner.in_flight -= 1;
+            let metrics = inner.rpcs.entry(self.rpc).or_default();
+            metrics.count += 1;
+            metrics.duration += self.start.elapsed();
+        }
+    }
+}
+
+/// Serve the metrics on `TF_PROVIDER_METRICS_ADDR` if set, when the provider is being debugged
+///
+/// The provider is being debugged when it has not been launched by Terraform,
+/// ie: when the magic cookie of the plugin protocol is not set, like with `TF_REATTACH_PROVIDERS`.
+#[cfg(feature = "metrics")]
+pub(crate) async fn serve_from_env(
+    server: &std::sync::Arc<crate::server::Server>,
+) -> anyhow::Result<impl std::future::Future<Output = anyhow::Result<()>>> {
+    use futures::future::{ok, Either};
+
+    let Ok(addr) = std::env::var("TF_PROVIDER_METRICS_ADDR") else {
+        return Ok(Either::Right(ok(())));
+    };
+    if std::env::var_os("TF_PLUGIN_MAGIC_COOKIE").is_some() {
+        tracing::warn!(
+            "Metrics are only served when debugging the provider, ignoring TF_PROVIDER_METRICS_ADDR"
+        );
+        return Ok(Either::Right(ok(())));
+    }
+    Ok(Either::Left(serve(server.clone(), &addr).await?))
+}
+
+/// Serve the metrics over HTTP on `addr`, until the server is stopped
+///
+/// Every request is answered with the metrics, whatever its path.
+/// The listener is bound before returning, so that an invalid address is reported right away.
+#[cfg(feature = "metrics")]
+async fn serve(
+    server: std::sync::Arc<crate::server::Server>,
+    addr: &str,
+) -> anyhow::Result<impl std::future::Future<Output = anyhow::Result<()>>> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    /// Maximum time given to a client to send its request and read the response
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!("Serving metrics on {}", listener.local_addr()?);
+
+    Ok(async move {
+        loop {
+            let (stream, _) = tokio::select! {
+                _ = server.cancellation_token.cancelled() => return Ok(()),
+                accepted = listener.accept() => match accepted {
+                    Ok(accepted) => accepted,
+                    Err(err) => {
+                        tracing::warn!("Could not accept metrics connection: {}", err);
+                        continue;
+                    }
+                },
+            };
+            let server = server.clone();
+            tokio::spawn(tokio::time::timeout(TIMEOUT, async move {
+                let mut stream = BufReader::new(stream);
+                // Consume the request headers
+                let mut line = String::new();
+                while stream.read_line(&mut line).await.is_ok_and(|n| n > 2) {
+                    line.clear();
+                }
+
+                let body = server.metrics.render();
+                _ = stream
+                    .write_all(
+                        format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            body.len(),
+                            body,
+                        )
+                        .as_bytes(),
+                    )
+                    .await;
+                _ = stream.shutdown().await;
+            }));
+        }
+    })
+}
@@ -0,0 +1,276 @@
+// This file is part of the tf-provider project
+//
+// Copyright (C) ANEO, 2024-2024. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Attribute plan modifiers
+//!
+//! Resources declare their plan modifiers with [`Resource::plan_modifiers`](crate::Resource::plan_modifiers),
+//! and the provider server applies them to the state planned by [`Resource::plan_update`](crate::Resource::plan_update),
+//! after the planner of the resource has run.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use crate::attribute_path::AttributePath;
+use crate::diagnostics::Diagnostics;
+use crate::raw::RawValue;
+use crate::utils::OptionFactor;
+use crate::value::ValueAny;
+
+/// Predicate of [`PlanModifier::RequiresReplaceIf`], called with the prior and the planned values
+pub type RequiresReplacePredicate = dyn Fn(&ValueAny, &ValueAny) -> bool + Send + Sync;
+
+/// Modifier of the planned value of an attribute
+#[derive(Clone)]
+pub enum PlanModifier {
+    /// Use the prior value if the planned value is unknown, and the attribute is not set in the configuration
+    UseStateForUnknown,
+    /// Like [`PlanModifier::UseStateForUnknown`], but only if the given input attributes are unchanged
+    /// between the prior and the planned states
+    ///
+    /// This avoids spurious diffs on attributes computed from inputs, like digests of a content.
+    UseStateIfUnchanged(Vec<AttributePath>),
+    /// Replace the resource if the planned value differs from the prior value
+    RequiresReplace,
+    /// Replace the resource if the planned value differs from the prior value,
+    /// and the predicate returns `true`
+    RequiresReplaceIf(Arc<RequiresReplacePredicate>),
+}
+
+impl PlanModifier {
+    /// Create a [`PlanModifier::RequiresReplaceIf`] modifier
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - Called with the prior and the planned values when they differ
+    pub fn requires_replace_if<F>(predicate: F) -> Self
+    where
+        F: Fn(&ValueAny, &ValueAny) -> bool + Send + Sync + 'static,
+    {
+        Self::RequiresReplaceIf(Arc::new(predicate))
+    }
+
+    /// Create a [`PlanModifier::UseStateIfUnchanged`] modifier
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - Paths of the attributes the modified attribute is computed from
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde_json::json;
+    /// # use tf_provider::plan_modifier::{apply_plan_modifiers, PlanModifier};
+    /// # use tf_provider::value::ValueAny;
+    /// # use tf_provider::{AttributePath, Diagnostics, RawValue};
+    /// let mut diags = Diagnostics::default();
+    /// let raw = |value: serde_json::Value| {
+    ///     let value = ValueAny::try_from(value).unwrap();
+    ///     RawValue::serialize(&mut Diagnostics::default(), &value).unwrap()
+    /// };
+    /// // Planned state with an unknown digest
+    /// let planned = |content: &str| {
+    ///     let mut state = ValueAny::try_from(json!({"content": content})).unwrap();
+    ///     let ValueAny::Map(map) = &mut state else { unreachable!() };
+    ///     map.insert("sha256".to_owned(), ValueAny::Unknown);
+    ///     RawValue::serialize(&mut Diagnostics::default(), &state).unwrap()
+    /// };
+    /// let modifiers = [(
+    ///     AttributePath::new("sha256"),
+    ///     PlanModifier::use_state_if_unchanged([AttributePath::new("content")]),
+    /// )];
+    /// let prior = raw(json!({"content": "hello", "sha256": "2cf24dba"}));
+    /// let config = raw(json!({"content": "hello", "sha256": null}));
+    ///
+    /// // Unchanged content: the digest is carried over, and there is no diff
+    /// let (state, _) = apply_plan_modifiers(
+    ///     &mut diags, &modifiers, &prior, &config, planned("hello"), vec![],
+    /// ).unwrap();
+    /// assert_eq!(state.deserialize::<ValueAny>(&mut diags), prior.deserialize(&mut diags));
+    ///
+    /// // Changed content: the digest stays unknown
+    /// let (state, _) = apply_plan_modifiers(
+    ///     &mut diags, &modifiers, &prior, &config, planned("world"), vec![],
+    /// ).unwrap();
+    /// assert_eq!(state, planned("world"));
+    ///
+    /// // Unknown prior digest (eg: freshly imported): the digest stays unknown
+    /// let prior = planned("hello");
+    /// let (state, _) = apply_plan_modifiers(
+    ///     &mut diags, &modifiers, &prior, &config, planned("hello"), vec![],
+    /// ).unwrap();
+    /// assert_eq!(state, planned("hello"));
+    /// assert!(!diags.has_errors());
+    /// ```
+    pub fn use_state_if_unchanged<I>(inputs: I) -> Self
+    where
+        I: IntoIterator<Item = AttributePath>,
+    {
+        Self::UseStateIfUnchanged(inputs.into_iter().collect())
+    }
+}
+
+impl Debug for PlanModifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UseStateForUnknown => f.write_str("UseStateForUnknown"),
+            Self::UseStateIfUnchanged(inputs) => {
+                f.debug_tuple("UseStateIfUnchanged").field(inputs).finish()
+            }
+            Self::RequiresReplace => f.write_str("RequiresReplace"),
+            Self::RequiresReplaceIf(_) => f.write_str("RequiresReplaceIf(..)"),
+        }
+    }
+}
+
+/// Apply plan modifiers to a planned state
+///
+/// [`PlanModifier::UseStateForUnknown`] and [`PlanModifier::UseStateIfUnchanged`] modifiers are applied first,
+/// so that replace modifiers see the carried prior values.
+///
+/// # Arguments
+///
+/// * `diags` - Diagnostics where serialization errors are reported
+/// * `modifiers` - Plan modifiers, with the path of the attribute they apply to
+/// * `prior_state` - State as stored in the Terraform state
+/// * `config_state` - State as declared in the Terraform file
+/// * `planned_state` - State planned by the resource
+/// * `triggers` - Replace triggers planned by the resource
+///
+/// # Remarks
+///
+/// Returns [`None`] iff there is an error reported in diagnostics.
+/// Nothing is done if there is no prior state or no planned state.
+/// A trigger is added at most once for a given path.
+///
+/// # Examples
+///
+/// ```
+/// # use serde_json::json;
+/// # use tf_provider::plan_modifier::{apply_plan_modifiers, PlanModifier};
+/// # use tf_provider::value::ValueAny;
+/// # use tf_provider::{AttributePath, Diagnostics, RawValue};
+/// let mut diags = Diagnostics::default();
+/// let raw = |value: serde_json::Value| {
+///     let value = ValueAny::try_from(value).unwrap();
+///     RawValue::serialize(&mut Diagnostics::default(), &value).unwrap()
+/// };
+/// // Planned state with an unknown id
+/// let planned = |path: &str, mode: &str| {
+///     let mut state = ValueAny::try_from(json!({"path": path, "mode": mode})).unwrap();
+///     let ValueAny::Map(map) = &mut state else { unreachable!() };
+///     map.insert("id".to_owned(), ValueAny::Unknown);
+///     RawValue::serialize(&mut Diagnostics::default(), &state).unwrap()
+/// };
+/// let prior = raw(json!({"id": "42", "path": "/tmp/a", "mode": "0644"}));
+/// let config = raw(json!({"id": null, "path": "/tmp/a", "mode": "0644"}));
+///
+/// // UseStateForUnknown: the unknown id is replaced by its prior value
+/// let modifiers = [(AttributePath::new("id"), PlanModifier::UseStateForUnknown)];
+/// let (state, triggers) = apply_plan_modifiers(
+///     &mut diags, &modifiers, &prior, &config, planned("/tmp/a", "0644"), vec![],
+/// ).unwrap();
+/// assert_eq!(state.deserialize::<ValueAny>(&mut diags), prior.deserialize(&mut diags));
+/// assert!(triggers.is_empty());
+///
+/// // RequiresReplace: a new path triggers a replacement
+/// let modifiers = [(AttributePath::new("path"), PlanModifier::RequiresReplace)];
+/// let (_, triggers) = apply_plan_modifiers(
+///     &mut diags, &modifiers, &prior, &config, planned("/tmp/b", "0644"), vec![],
+/// ).unwrap();
+/// assert_eq!(triggers, [AttributePath::new("path")]);
+///
+/// // RequiresReplaceIf: only making the file more permissive triggers a replacement
+/// let modifiers = [(
+///     AttributePath::new("mode"),
+///     PlanModifier::requires_replace_if(|prior, planned| match (prior, planned) {
+///         (ValueAny::String(prior), ValueAny::String(planned)) => planned > prior,
+///         _ => true,
+///     }),
+/// )];
+/// for (mode, replace) in [("0600", false), ("0644", false), ("0666", true)] {
+///     let (_, triggers) = apply_plan_modifiers(
+///         &mut diags, &modifiers, &prior, &config, planned("/tmp/a", mode), vec![],
+///     ).unwrap();
+///     assert_eq!(!triggers.is_empty(), replace);
+/// }
+/// assert!(!diags.has_errors());
+/// ```
+pub fn apply_plan_modifiers(
+    diags: &mut Diagnostics,
+    modifiers: &[(AttributePath, PlanModifier)],
+    prior_state: &RawValue,
+    config_state: &RawValue,
+    planned_state: RawValue,
+    mut triggers: Vec<AttributePath>,
+) -> Option<(RawValue, Vec<AttributePath>)> {
+    if modifiers.is_empty() || prior_state.is_null() || planned_state.is_null() {
+        return Some((planned_state, triggers));
+    }
+
+    let (prior, config, mut planned) = (
+        prior_state.deserialize::<ValueAny>(diags),
+        config_state.deserialize::<ValueAny>(diags),
+        planned_state.deserialize::<ValueAny>(diags),
+    )
+        .factor()?;
+
+    let mut modified = false;
+    for (path, modifier) in modifiers {
+        let unchanged = match modifier {
+            PlanModifier::UseStateForUnknown => true,
+            PlanModifier::UseStateIfUnchanged(inputs) => inputs.iter().all(|input| {
+                prior.get_path(input).unwrap_or(&ValueAny::Null)
+                    == planned.get_path(input).unwrap_or(&ValueAny::Null)
+            }),
+            PlanModifier::RequiresReplace | PlanModifier::RequiresReplaceIf(_) => false,
+        };
+        if unchanged {
+            let config = config.get_path(path).unwrap_or(&ValueAny::Null);
+            match (prior.get_path(path), planned.get_path_mut(path)) {
+                (Some(prior), Some(planned))
+                    if *planned == ValueAny::Unknown
+                        && *config == ValueAny::Null
+                        && *prior != ValueAny::Unknown =>
+                {
+                    *planned = prior.clone();
+                    modified = true;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    for (path, modifier) in modifiers {
+        let prior = prior.get_path(path).unwrap_or(&ValueAny::Null);
+        let planned = planned.get_path(path).unwrap_or(&ValueAny::Null);
+        let replace = match modifier {
+            PlanModifier::UseStateForUnknown | PlanModifier::UseStateIfUnchanged(_) => false,
+            PlanModifier::RequiresReplace => prior != planned,
+            PlanModifier::RequiresReplaceIf(predicate) => {
+                prior != planned && predicate(prior, planned)
+            }
+        };
+        if replace && !triggers.contains(path) {
+            triggers.push(path.clone());
+        }
+    }
+
+    if modified {
+        Some((RawValue::serialize(diags, &planned)?, triggers))
+    } else {
+        Some((planned_state, triggers))
+    }
+}
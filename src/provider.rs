@@ -17,6 +17,7 @@
 //! [`Provider`] module
 
 use std::collections::HashMap;
+use std::time::Duration;
 
 use crate::data_source::DynamicDataSource;
 use crate::diagnostics::Diagnostics;
@@ -132,6 +133,75 @@ pub trait Provider: Send + Sync + 'static {
         Some(HashMap::new())
     }
 
+    /// Get the aliases of the resources of the provider
+    ///
+    /// # Arguments
+    ///
+    /// * `diags` - Diagnostics to record warnings and errors that occured when getting back the aliases
+    ///
+    /// # Remarks
+    ///
+    /// Aliases map an alternative resource name to the name of a resource returned by [`Provider::get_resources`].
+    /// The alias resolves to the same resource, and its schema is marked as deprecated.
+    /// This enables renaming a resource without breaking existing configurations.
+    ///
+    /// The return is ignored if there is an error in diagnostics.
+    /// If the return is [`None`], an ad-hoc error is added to diagnostics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use async_trait::async_trait;
+    /// # use tf_provider::schema::{Block, Schema};
+    /// # use tf_provider::value::ValueEmpty;
+    /// # use tf_provider::{map, AttributePath, Diagnostics, DynamicResource, Provider, Resource};
+    /// struct File;
+    ///
+    /// #[async_trait]
+    /// impl Resource for File {
+    ///     type State<'a> = ValueEmpty;
+    ///     type PrivateState<'a> = ValueEmpty;
+    ///     type ProviderMetaState<'a> = ValueEmpty;
+    ///
+    ///     fn schema(&self, _diags: &mut Diagnostics) -> Option<Schema> {
+    ///         Some(Schema { version: 1, block: Block::default() })
+    ///     }
+    ///     // ...
+    /// #   async fn read<'a>(&self, _: &mut Diagnostics, state: ValueEmpty, private_state: ValueEmpty, _: ValueEmpty) -> Option<(ValueEmpty, ValueEmpty)> { Some((state, private_state)) }
+    /// #   async fn plan_create<'a>(&self, _: &mut Diagnostics, state: ValueEmpty, _: ValueEmpty, _: ValueEmpty) -> Option<(ValueEmpty, ValueEmpty)> { Some((state, Default::default())) }
+    /// #   async fn plan_update<'a>(&self, _: &mut Diagnostics, _: ValueEmpty, state: ValueEmpty, _: ValueEmpty, private_state: ValueEmpty, _: ValueEmpty) -> Option<(ValueEmpty, ValueEmpty, Vec<AttributePath>)> { Some((state, private_state, vec![])) }
+    /// #   async fn plan_destroy<'a>(&self, _: &mut Diagnostics, _: ValueEmpty, private_state: ValueEmpty, _: ValueEmpty) -> Option<ValueEmpty> { Some(private_state) }
+    /// #   async fn create<'a>(&self, _: &mut Diagnostics, state: ValueEmpty, _: ValueEmpty, private_state: ValueEmpty, _: ValueEmpty) -> Option<(ValueEmpty, ValueEmpty)> { Some((state, private_state)) }
+    /// #   async fn update<'a>(&self, _: &mut Diagnostics, _: ValueEmpty, state: ValueEmpty, _: ValueEmpty, private_state: ValueEmpty, _: ValueEmpty) -> Option<(ValueEmpty, ValueEmpty)> { Some((state, private_state)) }
+    /// #   async fn destroy<'a>(&self, _: &mut Diagnostics, _: ValueEmpty, _: ValueEmpty, _: ValueEmpty) -> Option<()> { Some(()) }
+    /// }
+    ///
+    /// struct Files;
+    ///
+    /// #[async_trait]
+    /// impl Provider for Files {
+    ///     type Config<'a> = ValueEmpty;
+    ///     type MetaState<'a> = ValueEmpty;
+    ///
+    ///     fn schema(&self, _diags: &mut Diagnostics) -> Option<Schema> {
+    ///         Some(Schema { version: 1, block: Block::default() })
+    ///     }
+    ///
+    ///     fn get_resources(&self, _diags: &mut Diagnostics) -> Option<HashMap<String, Box<dyn DynamicResource>>> {
+    ///         Some(map! { "file" => File })
+    ///     }
+    ///
+    ///     fn get_resource_aliases(&self, _diags: &mut Diagnostics) -> Option<HashMap<String, String>> {
+    ///         Some(map! { "cmd_file" => "file" })
+    ///     }
+    /// }
+    /// ```
+    fn get_resource_aliases(&self, diags: &mut Diagnostics) -> Option<HashMap<String, String>> {
+        _ = diags;
+        Some(HashMap::new())
+    }
+
     /// Get the data sources of the provider
     ///
     /// # Arguments
@@ -167,6 +237,16 @@ pub trait Provider: Send + Sync + 'static {
         _ = diags;
         Some(HashMap::new())
     }
+
+    /// Get the grace period given to in-flight operations when the provider is stopped
+    ///
+    /// When Terraform stops the provider, in-flight operations are given this much time to complete.
+    /// Operations still running afterwards are cancelled, and report an error.
+    ///
+    /// Defaults to 10 seconds.
+    fn stop_grace_period(&self) -> Duration {
+        Duration::from_secs(10)
+    }
 }
 
 /// Trait for implementing a provider *without* automatic serialization/deserialization
@@ -263,6 +343,25 @@ pub trait DynamicProvider: Send + Sync + 'static {
         Some(HashMap::new())
     }
 
+    /// Get the aliases of the resources of the provider
+    ///
+    /// # Arguments
+    ///
+    /// * `diags` - Diagnostics to record warnings and errors that occured when getting back the aliases
+    ///
+    /// # Remarks
+    ///
+    /// Aliases map an alternative resource name to the name of a resource returned by [`DynamicProvider::get_resources`].
+    /// The alias resolves to the same resource, and its schema is marked as deprecated.
+    /// This enables renaming a resource without breaking existing configurations.
+    ///
+    /// The return is ignored if there is an error in diagnostics.
+    /// If the return is [`None`], an ad-hoc error is added to diagnostics.
+    fn get_resource_aliases(&self, diags: &mut Diagnostics) -> Option<HashMap<String, String>> {
+        _ = diags;
+        Some(HashMap::new())
+    }
+
     /// Get the data sources of the provider
     ///
     /// # Arguments
@@ -298,6 +397,51 @@ pub trait DynamicProvider: Send + Sync + 'static {
         _ = diags;
         Some(HashMap::new())
     }
+
+    /// Get the grace period given to in-flight operations when the provider is stopped
+    ///
+    /// When Terraform stops the provider, in-flight operations are given this much time to complete.
+    /// Operations still running afterwards are cancelled, and report an error.
+    ///
+    /// Defaults to 10 seconds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use std::time::Duration;
+    /// # use async_trait::async_trait;
+    /// # use tf_provider::schema::{Block, FunctionSchema, Schema};
+    /// # use tf_provider::{Diagnostics, DynamicFunction, DynamicProvider, RawValue};
+    /// struct Slow;
+    ///
+    /// #[async_trait]
+    /// impl DynamicFunction for Slow {
+    ///     fn schema(&self, _diags: &mut Diagnostics) -> Option<FunctionSchema> {
+    ///         Some(FunctionSchema::default())
+    ///     }
+    ///     async fn call<'a>(&self, _diags: &mut Diagnostics, _params: Vec<RawValue>) -> Option<RawValue> {
+    ///         std::future::pending().await
+    ///     }
+    /// }
+    ///
+    /// struct SlowProvider;
+    ///
+    /// impl DynamicProvider for SlowProvider {
+    ///     fn schema(&self, _diags: &mut Diagnostics) -> Option<Schema> {
+    ///         Some(Schema { version: 1, block: Block::default() })
+    ///     }
+    ///     fn get_functions(&self, _diags: &mut Diagnostics) -> Option<HashMap<String, Box<dyn DynamicFunction>>> {
+    ///         Some(HashMap::from([("slow".to_owned(), Box::new(Slow) as Box<dyn DynamicFunction>)]))
+    ///     }
+    ///     fn stop_grace_period(&self) -> Duration {
+    ///         Duration::from_secs(1)
+    ///     }
+    /// }
+    /// ```
+    fn stop_grace_period(&self) -> Duration {
+        Duration::from_secs(10)
+    }
 }
 
 #[async_trait]
@@ -337,6 +481,11 @@ impl<T: Provider> DynamicProvider for T {
         <T as Provider>::get_resources(self, diags)
     }
 
+    /// Get the aliases of the resources of the provider
+    fn get_resource_aliases(&self, diags: &mut Diagnostics) -> Option<HashMap<String, String>> {
+        <T as Provider>::get_resource_aliases(self, diags)
+    }
+
     /// Get the data sources of the provider
     fn get_data_sources(
         &self,
@@ -352,6 +501,11 @@ impl<T: Provider> DynamicProvider for T {
     ) -> Option<HashMap<String, Box<dyn DynamicFunction>>> {
         <T as Provider>::get_functions(self, diags)
     }
+
+    /// Get the grace period given to in-flight operations when the provider is stopped
+    fn stop_grace_period(&self) -> Duration {
+        <T as Provider>::stop_grace_period(self)
+    }
 }
 
 impl<T: Provider + 'static> From<T> for Box<dyn DynamicProvider> {
@@ -359,3 +513,346 @@ impl<T: Provider + 'static> From<T> for Box<dyn DynamicProvider> {
         Box::new(value)
     }
 }
+
+/// Builder of the named items of a provider: resources, data sources, or functions
+///
+/// Contrary to building a [`HashMap`] directly, a name cannot be registered twice,
+/// and names must follow the Terraform naming rules:
+/// lowercase letters, digits, and underscores, starting with a letter.
+///
+/// # Examples
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use async_trait::async_trait;
+/// # use tf_provider::schema::{Block, Schema};
+/// # use tf_provider::value::ValueEmpty;
+/// # use tf_provider::{AttributePath, Diagnostics, DynamicResource, Resource, ResourceRegistry};
+/// struct File;
+///
+/// #[async_trait]
+/// impl Resource for File {
+///     type State<'a> = ValueEmpty;
+///     type PrivateState<'a> = ValueEmpty;
+///     type ProviderMetaState<'a> = ValueEmpty;
+///
+///     fn schema(&self, _diags: &mut Diagnostics) -> Option<Schema> {
+///         Some(Schema { version: 1, block: Block::default() })
+///     }
+///     // ...
+/// #   async fn read<'a>(&self, _: &mut Diagnostics, state: ValueEmpty, private_state: ValueEmpty, _: ValueEmpty) -> Option<(ValueEmpty, ValueEmpty)> { Some((state, private_state)) }
+/// #   async fn plan_create<'a>(&self, _: &mut Diagnostics, state: ValueEmpty, _: ValueEmpty, _: ValueEmpty) -> Option<(ValueEmpty, ValueEmpty)> { Some((state, Default::default())) }
+/// #   async fn plan_update<'a>(&self, _: &mut Diagnostics, _: ValueEmpty, state: ValueEmpty, _: ValueEmpty, private_state: ValueEmpty, _: ValueEmpty) -> Option<(ValueEmpty, ValueEmpty, Vec<AttributePath>)> { Some((state, private_state, vec![])) }
+/// #   async fn plan_destroy<'a>(&self, _: &mut Diagnostics, _: ValueEmpty, private_state: ValueEmpty, _: ValueEmpty) -> Option<ValueEmpty> { Some(private_state) }
+/// #   async fn create<'a>(&self, _: &mut Diagnostics, state: ValueEmpty, _: ValueEmpty, private_state: ValueEmpty, _: ValueEmpty) -> Option<(ValueEmpty, ValueEmpty)> { Some((state, private_state)) }
+/// #   async fn update<'a>(&self, _: &mut Diagnostics, _: ValueEmpty, state: ValueEmpty, _: ValueEmpty, private_state: ValueEmpty, _: ValueEmpty) -> Option<(ValueEmpty, ValueEmpty)> { Some((state, private_state)) }
+/// #   async fn destroy<'a>(&self, _: &mut Diagnostics, _: ValueEmpty, _: ValueEmpty, _: ValueEmpty) -> Option<()> { Some(()) }
+/// }
+///
+/// fn get_resources(diags: &mut Diagnostics) -> Option<HashMap<String, Box<dyn DynamicResource>>> {
+///     let mut registry = ResourceRegistry::new();
+///     registry.add(diags, "file", File)?.add(diags, "cmd_file", File)?;
+///     Some(registry.build())
+/// }
+///
+/// let mut diags = Diagnostics::default();
+/// let resources = get_resources(&mut diags).unwrap();
+/// assert_eq!(resources.len(), 2);
+/// assert!(diags.errors.is_empty());
+///
+/// // Duplicate names are reported
+/// let mut registry = ResourceRegistry::new();
+/// registry.add(&mut diags, "file", File).unwrap();
+/// assert!(registry.add(&mut diags, "file", File).is_none());
+/// assert_eq!(diags.errors[0].summary, "Duplicate name");
+///
+/// // Invalid names are reported
+/// let mut diags = Diagnostics::default();
+/// assert!(registry.add(&mut diags, "Cmd-File", File).is_none());
+/// assert_eq!(diags.errors[0].summary, "Invalid name");
+/// assert_eq!(registry.build().len(), 1);
+/// ```
+pub struct Registry<T: ?Sized> {
+    items: HashMap<String, Box<T>>,
+}
+
+/// Builder of the resources of a provider, see [`Provider::get_resources`]
+pub type ResourceRegistry = Registry<dyn DynamicResource>;
+/// Builder of the data sources of a provider, see [`Provider::get_data_sources`]
+pub type DataSourceRegistry = Registry<dyn DynamicDataSource>;
+/// Builder of the functions of a provider, see [`Provider::get_functions`]
+pub type FunctionRegistry = Registry<dyn DynamicFunction>;
+
+impl<T: ?Sized> Registry<T> {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self {
+            items: HashMap::new(),
+        }
+    }
+
+    /// Register an item under `name`
+    ///
+    /// # Arguments
+    ///
+    /// * `diags` - Diagnostics where duplicate and invalid names are reported
+    /// * `name` - Name of the item, without the prefix of the provider
+    /// * `item` - Item to register
+    ///
+    /// # Remarks
+    ///
+    /// Returns [`None`] iff there is an error reported in diagnostics.
+    /// In that case, the item is not registered.
+    pub fn add<N, V>(&mut self, diags: &mut Diagnostics, name: N, item: V) -> Option<&mut Self>
+    where
+        N: Into<String>,
+        V: Into<Box<T>>,
+    {
+        let name = name.into();
+        let mut chars = name.chars();
+        let valid = chars.next().is_some_and(|c| c.is_ascii_lowercase())
+            && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !valid {
+            diags.root_error(
+                "Invalid name",
+                format!("`{name}` must only contain lowercase letters, digits, and underscores, and start with a letter"),
+            );
+            return None;
+        }
+        if self.items.contains_key(&name) {
+            diags.root_error(
+                "Duplicate name",
+                format!("`{name}` is registered more than once"),
+            );
+            return None;
+        }
+        self.items.insert(name, item.into());
+        Some(self)
+    }
+
+    /// Get the registered items by name
+    pub fn build(self) -> HashMap<String, Box<T>> {
+        self.items
+    }
+}
+
+impl<T: ?Sized> Default for Registry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::{Duration, Instant};
+
+    use async_trait::async_trait;
+
+    use crate::schema::{Block, FunctionSchema, Schema};
+    use crate::tfplugin6::call_function;
+    use crate::tfplugin6_client::ProviderClient;
+    use crate::value::ValueEmpty;
+    use crate::{
+        map, AttributePath, Diagnostics, DynamicFunction, DynamicProvider, DynamicResource,
+        Provider, RawValue, Resource,
+    };
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+    }
+
+    struct File;
+
+    #[async_trait]
+    impl Resource for File {
+        type State<'a> = ValueEmpty;
+        type PrivateState<'a> = ValueEmpty;
+        type ProviderMetaState<'a> = ValueEmpty;
+
+        fn schema(&self, _: &mut Diagnostics) -> Option<Schema> {
+            Some(Schema {
+                version: 1,
+                block: Block::default(),
+            })
+        }
+        async fn read<'a>(
+            &self,
+            _: &mut Diagnostics,
+            state: ValueEmpty,
+            private_state: ValueEmpty,
+            _: ValueEmpty,
+        ) -> Option<(ValueEmpty, ValueEmpty)> {
+            Some((state, private_state))
+        }
+        async fn plan_create<'a>(
+            &self,
+            _: &mut Diagnostics,
+            state: ValueEmpty,
+            _: ValueEmpty,
+            _: ValueEmpty,
+        ) -> Option<(ValueEmpty, ValueEmpty)> {
+            Some((state, Default::default()))
+        }
+        async fn plan_update<'a>(
+            &self,
+            _: &mut Diagnostics,
+            _: ValueEmpty,
+            state: ValueEmpty,
+            _: ValueEmpty,
+            private_state: ValueEmpty,
+            _: ValueEmpty,
+        ) -> Option<(ValueEmpty, ValueEmpty, Vec<AttributePath>)> {
+            Some((state, private_state, vec![]))
+        }
+        async fn plan_destroy<'a>(
+            &self,
+            _: &mut Diagnostics,
+            _: ValueEmpty,
+            private_state: ValueEmpty,
+            _: ValueEmpty,
+        ) -> Option<ValueEmpty> {
+            Some(private_state)
+        }
+        async fn create<'a>(
+            &self,
+            _: &mut Diagnostics,
+            state: ValueEmpty,
+            _: ValueEmpty,
+            private_state: ValueEmpty,
+            _: ValueEmpty,
+        ) -> Option<(ValueEmpty, ValueEmpty)> {
+            Some((state, private_state))
+        }
+        async fn update<'a>(
+            &self,
+            _: &mut Diagnostics,
+            _: ValueEmpty,
+            state: ValueEmpty,
+            _: ValueEmpty,
+            private_state: ValueEmpty,
+            _: ValueEmpty,
+        ) -> Option<(ValueEmpty, ValueEmpty)> {
+            Some((state, private_state))
+        }
+        async fn destroy<'a>(
+            &self,
+            _: &mut Diagnostics,
+            _: ValueEmpty,
+            _: ValueEmpty,
+            _: ValueEmpty,
+        ) -> Option<()> {
+            Some(())
+        }
+    }
+
+    struct Files;
+
+    #[async_trait]
+    impl Provider for Files {
+        type Config<'a> = ValueEmpty;
+        type MetaState<'a> = ValueEmpty;
+
+        fn schema(&self, _: &mut Diagnostics) -> Option<Schema> {
+            Some(Schema {
+                version: 1,
+                block: Block::default(),
+            })
+        }
+        fn get_resources(
+            &self,
+            _: &mut Diagnostics,
+        ) -> Option<HashMap<String, Box<dyn DynamicResource>>> {
+            Some(map! { "file" => File })
+        }
+        fn get_resource_aliases(&self, _: &mut Diagnostics) -> Option<HashMap<String, String>> {
+            Some(map! { "cmd_file" => "file" })
+        }
+    }
+
+    #[test]
+    fn resource_alias_is_deprecated() {
+        runtime().block_on(async {
+            let mut client = ProviderClient::spawn("files", Box::new(Files)).await;
+            let response = client.get_provider_schema().await;
+            assert!(response.diagnostics.is_empty());
+            let deprecated = |name: &str| {
+                response.resource_schemas[name]
+                    .block
+                    .as_ref()
+                    .unwrap()
+                    .deprecated
+            };
+            assert!(!deprecated("files_file"));
+            assert!(deprecated("files_cmd_file"));
+        });
+    }
+
+    struct Slow;
+
+    #[async_trait]
+    impl DynamicFunction for Slow {
+        fn schema(&self, _: &mut Diagnostics) -> Option<FunctionSchema> {
+            Some(FunctionSchema::default())
+        }
+        async fn call<'a>(&self, _: &mut Diagnostics, _: Vec<RawValue>) -> Option<RawValue> {
+            std::future::pending().await
+        }
+    }
+
+    struct SlowProvider;
+
+    impl DynamicProvider for SlowProvider {
+        fn schema(&self, _: &mut Diagnostics) -> Option<Schema> {
+            Some(Schema {
+                version: 1,
+                block: Block::default(),
+            })
+        }
+        fn get_functions(
+            &self,
+            _: &mut Diagnostics,
+        ) -> Option<HashMap<String, Box<dyn DynamicFunction>>> {
+            Some(HashMap::from([(
+                "slow".to_owned(),
+                Box::new(Slow) as Box<dyn DynamicFunction>,
+            )]))
+        }
+        fn stop_grace_period(&self) -> Duration {
+            Duration::from_secs(1)
+        }
+    }
+
+    #[test]
+    fn stop_cancels_operations_after_grace_period() {
+        runtime().block_on(async {
+            let mut client = ProviderClient::spawn("slow", Box::new(SlowProvider)).await;
+
+            // Start a call that never completes on its own
+            let mut caller = client.clone();
+            let call = tokio::spawn(async move {
+                let request = call_function::Request {
+                    name: "slow".to_owned(),
+                    arguments: vec![],
+                };
+                caller.call_function(request).await
+            });
+            tokio::time::sleep(Duration::from_millis(50)).await;
+
+            // Stopping returns right away, and the call is cancelled after the grace period
+            let start = Instant::now();
+            assert_eq!(client.stop_provider().await.error, "");
+            assert!(start.elapsed() < Duration::from_millis(500));
+
+            let response = tokio::time::timeout(Duration::from_secs(5), call)
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(start.elapsed() >= Duration::from_secs(1));
+            assert!(response.error.unwrap().text.contains("Operation cancelled"));
+        });
+    }
+}
@@ -16,7 +16,7 @@
 
 //! [`RawValue`] module
 
-use crate::{diagnostics::Diagnostics, tfplugin6};
+use crate::{attribute_path::AttributePath, diagnostics::Diagnostics, tfplugin6, value::ValueAny};
 use serde::{Deserialize, Serialize};
 
 /// Encode a dynamic value with either Message Pack or JSON encoding
@@ -30,6 +30,8 @@ pub enum RawValue {
 
 const NULL_MESSAGE_PACK: [u8; 1] = [0xc0_u8];
 const NULL_JSON: &str = "null";
+const EMPTY_MESSAGE_PACK: [u8; 1] = [0x80_u8];
+const EMPTY_JSON: &str = "{}";
 
 impl RawValue {
     /// Check if the encoded value is null
@@ -48,19 +50,45 @@ impl RawValue {
     ///
     /// # Remarks
     ///
-    /// Returns [`None`] iff there is an error reported in diagnostics
+    /// Returns [`None`] iff there is an error reported in diagnostics.
+    ///
+    /// An empty encoding (eg: the private state of a resource being created) is decoded as null.
+    /// If `T` does not accept null, it is decoded as an empty map instead,
+    /// so that structs with `#[serde(default)]` are decoded to their default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde::Deserialize;
+    /// # use tf_provider::{Diagnostics, RawValue};
+    /// # use tf_provider::value::Value;
+    /// #[derive(Debug, Default, PartialEq, Deserialize)]
+    /// #[serde(default)]
+    /// struct PrivateState {
+    ///     generation: u64,
+    ///     tags: Vec<String>,
+    ///     owner: Value<String>,
+    /// }
+    ///
+    /// let mut diags = Diagnostics::default();
+    /// let empty = RawValue::MessagePack(vec![]);
+    /// assert_eq!(empty.deserialize::<PrivateState>(&mut diags), Some(PrivateState::default()));
+    /// assert_eq!(empty.deserialize::<Value<u64>>(&mut diags), Some(Value::Null));
+    /// assert!(diags.errors.is_empty());
+    /// ```
     pub fn deserialize<'a, T>(&'a self, diags: &mut Diagnostics) -> Option<T>
     where
         T: Deserialize<'a>,
     {
         match self {
             Self::MessagePack(mp) => {
-                let slice = if mp.is_empty() {
-                    &NULL_MESSAGE_PACK
+                let result = if mp.is_empty() {
+                    rmp_serde::from_slice::<T>(&NULL_MESSAGE_PACK)
+                        .or_else(|_| rmp_serde::from_slice::<T>(&EMPTY_MESSAGE_PACK))
                 } else {
-                    mp.as_slice()
+                    rmp_serde::from_slice::<T>(mp.as_slice())
                 };
-                match rmp_serde::from_slice::<T>(slice) {
+                match result {
                     Ok(value) => Some(value),
                     Err(err) => {
                         diags.root_error_short(err.to_string());
@@ -69,12 +97,13 @@ impl RawValue {
                 }
             }
             Self::Json(json) => {
-                let slice = if json.is_empty() {
-                    NULL_JSON.as_bytes()
+                let result = if json.is_empty() {
+                    serde_json::from_str::<T>(NULL_JSON)
+                        .or_else(|_| serde_json::from_str::<T>(EMPTY_JSON))
                 } else {
-                    json.as_slice()
+                    serde_json::from_slice::<T>(json.as_slice())
                 };
-                match serde_json::from_slice::<T>(slice) {
+                match result {
                     Ok(value) => Some(value),
                     Err(err) => {
                         diags.root_error_short(err.to_string());
@@ -124,6 +153,102 @@ impl RawValue {
     {
         Some(Self::MessagePack(Self::serialize_vec(diags, value)?))
     }
+
+    /// Redact the values at the given paths
+    ///
+    /// Redacted values are replaced by the `"***"` marker, and the encoding is preserved.
+    /// Paths that do not exist, or that point to null or unknown values, are ignored.
+    /// If there is nothing to redact, the value is left untouched.
+    /// If the value cannot be decoded, the whole value is redacted.
+    ///
+    /// # Arguments
+    ///
+    /// * `paths` - paths of the values to redact
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde_json::json;
+    /// # use tf_provider::{AttributePath, Diagnostics, RawValue};
+    /// # use tf_provider::value::{Number, ValueAny};
+    /// let mut diags = Diagnostics::default();
+    /// let value = ValueAny::try_from(json!({
+    ///     "host": "example.com",
+    ///     "auth": [{"user": "root", "password": "secret"}],
+    /// })).unwrap();
+    ///
+    /// let mut raw = RawValue::serialize(&mut diags, &value).unwrap();
+    /// raw.redact(&[AttributePath::new("auth").index(0).attribute("password")]);
+    ///
+    /// let redacted: ValueAny = raw.deserialize(&mut diags).unwrap();
+    /// assert_eq!(redacted, ValueAny::try_from(json!({
+    ///     "host": "example.com",
+    ///     "auth": [{"user": "root", "password": "***"}],
+    /// })).unwrap());
+    ///
+    /// // Dynamic values and large numbers are preserved
+    /// let value = ValueAny::Map([
+    ///     ("token".to_owned(), ValueAny::String("secret".into())),
+    ///     ("size".to_owned(), ValueAny::Number(Number::Int(1 << 100))),
+    ///     ("extra".to_owned(), ValueAny::Dynamic(json!(["object", {"key": "string"}]), Box::new(
+    ///         ValueAny::Map([("key".to_owned(), ValueAny::String("secret".into()))].into()),
+    ///     ))),
+    /// ].into());
+    /// let mut raw = RawValue::serialize(&mut diags, &value).unwrap();
+    /// raw.redact(&[AttributePath::new("token"), AttributePath::new("extra").attribute("key")]);
+    ///
+    /// let redacted = ValueAny::Map([
+    ///     ("token".to_owned(), ValueAny::String("***".into())),
+    ///     ("size".to_owned(), ValueAny::Number(Number::Int(1 << 100))),
+    ///     ("extra".to_owned(), ValueAny::Dynamic(json!(["object", {"key": "string"}]), Box::new(
+    ///         ValueAny::Map([("key".to_owned(), ValueAny::String("***".into()))].into()),
+    ///     ))),
+    /// ].into());
+    /// assert_eq!(raw, RawValue::serialize(&mut diags, &redacted).unwrap());
+    ///
+    /// // The value is untouched when there is nothing to redact
+    /// let raw = RawValue::serialize(&mut diags, &value).unwrap();
+    /// let mut redacted = raw.clone();
+    /// redacted.redact(&[AttributePath::new("missing")]);
+    /// assert_eq!(redacted, raw);
+    /// assert!(!diags.has_errors());
+    /// ```
+    pub fn redact(&mut self, paths: &[AttributePath]) {
+        const MARKER: &str = "***";
+
+        let mut diags = Diagnostics::default();
+        let Some(mut value) = self.deserialize::<ValueAny>(&mut diags) else {
+            *self = match self {
+                Self::MessagePack(_) => {
+                    Self::MessagePack(rmp_serde::to_vec(MARKER).unwrap_or_default())
+                }
+                Self::Json(_) => Self::Json(serde_json::to_vec(MARKER).unwrap_or_default()),
+            };
+            return;
+        };
+
+        let mut redacted = false;
+        for path in paths {
+            match value.get_path_mut(path) {
+                None | Some(ValueAny::Null) | Some(ValueAny::Unknown) => (),
+                Some(target) => {
+                    *target = ValueAny::String(MARKER.to_owned());
+                    redacted = true;
+                }
+            }
+        }
+        if !redacted {
+            return;
+        }
+
+        let encoded = match self {
+            Self::MessagePack(_) => Self::serialize_vec(&mut diags, &value).map(Self::MessagePack),
+            Self::Json(_) => serde_json::to_vec(&value).ok().map(Self::Json),
+        };
+        if let Some(encoded) = encoded {
+            *self = encoded;
+        }
+    }
 }
 
 impl From<tfplugin6::DynamicValue> for RawValue {
@@ -18,6 +18,7 @@
 
 use crate::attribute_path::AttributePath;
 use crate::diagnostics::Diagnostics;
+use crate::plan_modifier::PlanModifier;
 use crate::raw::RawValue;
 use crate::schema::Schema;
 use crate::utils::OptionFactor;
@@ -162,6 +163,19 @@ pub trait Resource: Send + Sync {
         provider_meta_state: Self::ProviderMetaState<'a>,
     ) -> Option<Self::PrivateState<'a>>;
 
+    /// Get the plan modifiers of the resource attributes
+    ///
+    /// # Remarks
+    ///
+    /// The modifiers are applied to the state planned by [`Resource::plan_update`], after it returns.
+    /// They are not applied to the state planned by [`Resource::plan_create`], as there is no prior state to carry.
+    /// They run after the planner of the resource, rather than before:
+    /// Terraform proposes the prior values of the computed attributes that are not set in the configuration,
+    /// so the only unknown values to replace are the ones planned by the resource.
+    fn plan_modifiers(&self) -> Vec<(AttributePath, PlanModifier)> {
+        Vec::new()
+    }
+
     /// Create a new resource
     ///
     /// # Arguments
@@ -278,6 +292,128 @@ pub trait Resource: Send + Sync {
         diags.root_error_short("Upgrade is not supported");
         None
     }
+
+    /// Check if the resource can be moved from another resource type
+    ///
+    /// # Remarks
+    ///
+    /// Must return `true` for [`Resource::move_state`] to be called.
+    /// Terraform is told that the provider supports moving resources
+    /// if at least one resource can be moved.
+    fn can_move_state(&self) -> bool {
+        false
+    }
+
+    /// Move a resource from another resource type
+    ///
+    /// # Arguments
+    ///
+    /// * `diags` - Diagnostics to record warnings and errors that occured during the move
+    /// * `source_provider_address` - Address of the provider the resource is being moved from
+    /// * `source_type_name` - Resource type the resource is being moved from
+    /// * `source_schema_version` - Schema version of the resource type the resource is being moved from
+    /// * `source_state` - State of the resource being moved
+    /// * `source_private_state` - Private state of the resource being moved
+    ///
+    /// # Remarks
+    ///
+    /// If the return is [`None`], an ad-hoc error is added to diagnostics.
+    ///
+    /// Because the source state follows the schema of the source resource type,
+    /// it must be deserialized explicitely.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use async_trait::async_trait;
+    /// # use serde::{Deserialize, Serialize};
+    /// # use serde_json::json;
+    /// # use tf_provider::schema::Schema;
+    /// # use tf_provider::value::{ValueEmpty, ValueString};
+    /// # use tf_provider::{AttributePath, Diagnostics, DynamicResource, RawValue, Resource};
+    /// #[derive(Debug, Serialize, Deserialize)]
+    /// struct LegacyState {
+    ///     filename: ValueString<'static>,
+    /// }
+    ///
+    /// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    /// struct State {
+    ///     path: ValueString<'static>,
+    /// }
+    ///
+    /// struct File;
+    ///
+    /// #[async_trait]
+    /// impl Resource for File {
+    ///     type State<'a> = State;
+    ///     type PrivateState<'a> = ValueEmpty;
+    ///     type ProviderMetaState<'a> = ValueEmpty;
+    ///
+    ///     fn can_move_state(&self) -> bool {
+    ///         true
+    ///     }
+    ///
+    ///     async fn move_state<'a>(
+    ///         &self,
+    ///         diags: &mut Diagnostics,
+    ///         _source_provider_address: String,
+    ///         source_type_name: String,
+    ///         _source_schema_version: i64,
+    ///         source_state: RawValue,
+    ///         _source_private_state: Vec<u8>,
+    ///     ) -> Option<(State, ValueEmpty)> {
+    ///         if source_type_name != "legacy_file" {
+    ///             diags.root_error_short(format!("Cannot move from `{source_type_name}`"));
+    ///             return None;
+    ///         }
+    ///         let legacy: LegacyState = source_state.deserialize(diags)?;
+    ///         Some((State { path: legacy.filename }, Default::default()))
+    ///     }
+    ///
+    /// #   fn schema(&self, _: &mut Diagnostics) -> Option<Schema> { None }
+    /// #   async fn read<'a>(&self, _: &mut Diagnostics, state: State, private_state: ValueEmpty, _: ValueEmpty) -> Option<(State, ValueEmpty)> { Some((state, private_state)) }
+    /// #   async fn plan_create<'a>(&self, _: &mut Diagnostics, state: State, _: State, _: ValueEmpty) -> Option<(State, ValueEmpty)> { Some((state, Default::default())) }
+    /// #   async fn plan_update<'a>(&self, _: &mut Diagnostics, _: State, state: State, _: State, private_state: ValueEmpty, _: ValueEmpty) -> Option<(State, ValueEmpty, Vec<AttributePath>)> { Some((state, private_state, vec![])) }
+    /// #   async fn plan_destroy<'a>(&self, _: &mut Diagnostics, _: State, private_state: ValueEmpty, _: ValueEmpty) -> Option<ValueEmpty> { Some(private_state) }
+    /// #   async fn create<'a>(&self, _: &mut Diagnostics, state: State, _: State, private_state: ValueEmpty, _: ValueEmpty) -> Option<(State, ValueEmpty)> { Some((state, private_state)) }
+    /// #   async fn update<'a>(&self, _: &mut Diagnostics, _: State, state: State, _: State, private_state: ValueEmpty, _: ValueEmpty) -> Option<(State, ValueEmpty)> { Some((state, private_state)) }
+    /// #   async fn destroy<'a>(&self, _: &mut Diagnostics, _: State, _: ValueEmpty, _: ValueEmpty) -> Option<()> { Some(()) }
+    ///     // ...
+    /// }
+    ///
+    /// let mut diags = Diagnostics::default();
+    /// let source_state = RawValue::Json(json!({"filename": "/tmp/foo"}).to_string().into_bytes());
+    /// let (state, _) = futures::executor::block_on(DynamicResource::move_state(
+    ///     &File,
+    ///     &mut diags,
+    ///     "registry.terraform.io/example/legacy".into(),
+    ///     "legacy_file".into(),
+    ///     0,
+    ///     source_state,
+    ///     vec![],
+    /// ))
+    /// .unwrap();
+    /// let state: State = state.deserialize(&mut diags).unwrap();
+    /// assert_eq!(state, State { path: "/tmp/foo".into() });
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    async fn move_state<'a>(
+        &self,
+        diags: &mut Diagnostics,
+        source_provider_address: String,
+        source_type_name: String,
+        source_schema_version: i64,
+        source_state: RawValue,
+        source_private_state: Vec<u8>,
+    ) -> Option<(Self::State<'a>, Self::PrivateState<'a>)> {
+        _ = source_provider_address;
+        _ = source_type_name;
+        _ = source_schema_version;
+        _ = source_state;
+        _ = source_private_state;
+        diags.root_error_short("Move is not supported");
+        None
+    }
 }
 
 /// Trait for implementing a resource *without* automatic serialization/deserialization
@@ -402,6 +538,19 @@ pub trait DynamicResource: Send + Sync {
         provider_meta_state: RawValue,
     ) -> Option<Vec<u8>>;
 
+    /// Get the plan modifiers of the resource attributes
+    ///
+    /// # Remarks
+    ///
+    /// The modifiers are applied to the state planned by [`DynamicResource::plan_update`], after it returns.
+    /// They are not applied to the state planned by [`DynamicResource::plan_create`], as there is no prior state to carry.
+    /// They run after the planner of the resource, rather than before:
+    /// Terraform proposes the prior values of the computed attributes that are not set in the configuration,
+    /// so the only unknown values to replace are the ones planned by the resource.
+    fn plan_modifiers(&self) -> Vec<(AttributePath, PlanModifier)> {
+        Vec::new()
+    }
+
     /// Create a new resource
     ///
     /// # Arguments
@@ -511,6 +660,50 @@ pub trait DynamicResource: Send + Sync {
         diags.root_error_short("Upgrade is not supported");
         None
     }
+
+    /// Check if the resource can be moved from another resource type
+    ///
+    /// # Remarks
+    ///
+    /// Must return `true` for [`DynamicResource::move_state`] to be called.
+    /// Terraform is told that the provider supports moving resources
+    /// if at least one resource can be moved.
+    fn can_move_state(&self) -> bool {
+        false
+    }
+
+    /// Move a resource from another resource type
+    ///
+    /// # Arguments
+    ///
+    /// * `diags` - Diagnostics to record warnings and errors that occured during the move
+    /// * `source_provider_address` - Address of the provider the resource is being moved from
+    /// * `source_type_name` - Resource type the resource is being moved from
+    /// * `source_schema_version` - Schema version of the resource type the resource is being moved from
+    /// * `source_state` - State of the resource being moved
+    /// * `source_private_state` - Private state of the resource being moved
+    ///
+    /// # Remarks
+    ///
+    /// If the return is [`None`], an ad-hoc error is added to diagnostics.
+    #[allow(clippy::too_many_arguments)]
+    async fn move_state(
+        &self,
+        diags: &mut Diagnostics,
+        source_provider_address: String,
+        source_type_name: String,
+        source_schema_version: i64,
+        source_state: RawValue,
+        source_private_state: Vec<u8>,
+    ) -> Option<(RawValue, Vec<u8>)> {
+        _ = source_provider_address;
+        _ = source_type_name;
+        _ = source_schema_version;
+        _ = source_state;
+        _ = source_private_state;
+        diags.root_error_short("Move is not supported");
+        None
+    }
 }
 
 #[async_trait]
@@ -757,6 +950,40 @@ impl<T: Resource> DynamicResource for T {
         let state = <T as Resource>::upgrade(self, diags, version, prior_state).await?;
         RawValue::serialize(diags, &state)
     }
+    /// Get the plan modifiers of the resource attributes
+    fn plan_modifiers(&self) -> Vec<(AttributePath, PlanModifier)> {
+        <T as Resource>::plan_modifiers(self)
+    }
+    /// Check if the resource can be moved
+    fn can_move_state(&self) -> bool {
+        <T as Resource>::can_move_state(self)
+    }
+    /// Move the resource from another resource type
+    async fn move_state(
+        &self,
+        diags: &mut Diagnostics,
+        source_provider_address: String,
+        source_type_name: String,
+        source_schema_version: i64,
+        source_state: RawValue,
+        source_private_state: Vec<u8>,
+    ) -> Option<(RawValue, Vec<u8>)> {
+        let (state, private_state) = <T as Resource>::move_state(
+            self,
+            diags,
+            source_provider_address,
+            source_type_name,
+            source_schema_version,
+            source_state,
+            source_private_state,
+        )
+        .await?;
+        (
+            RawValue::serialize(diags, &state),
+            RawValue::serialize_vec(diags, &private_state),
+        )
+            .factor()
+    }
 }
 
 impl<T: Resource + 'static> From<T> for Box<dyn DynamicResource> {
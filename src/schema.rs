@@ -14,11 +14,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::HashMap, fmt::Display, hash::Hash};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    hash::Hash,
+};
 
 use serde::{ser::SerializeMap, Serialize};
 
-use crate::tfplugin6;
+use crate::{
+    attribute_path::AttributePath,
+    tfplugin6,
+    value::{Number, ValueAny},
+    Diagnostics, RawValue,
+};
 
 /// Specify if a description must interpreted as markdown or plain
 #[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
@@ -31,6 +41,35 @@ pub enum StringKind {
     Markdown = 1,
 }
 
+impl From<StringKind> for i32 {
+    fn from(value: StringKind) -> Self {
+        value as i32
+    }
+}
+
+impl TryFrom<i32> for StringKind {
+    type Error = i32;
+
+    /// Convert the protocol representation of a [`StringKind`] back
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::schema::StringKind;
+    /// for kind in [StringKind::Plain, StringKind::Markdown] {
+    ///     assert_eq!(StringKind::try_from(i32::from(kind.clone())), Ok(kind));
+    /// }
+    /// assert_eq!(StringKind::try_from(2), Err(2));
+    /// ```
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(StringKind::Plain),
+            1 => Ok(StringKind::Markdown),
+            _ => Err(value),
+        }
+    }
+}
+
 /// Description
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct Description {
@@ -69,6 +108,105 @@ impl Description {
             content: content.to_string(),
         }
     }
+
+    /// Get the content of the description as plain text
+    ///
+    /// Markdown descriptions are downgraded to plain text by stripping basic markdown:
+    /// emphasis (`*` and `_`), code spans (backticks), and links (only the text of the link is kept).
+    /// The content of code spans is kept as is, and so are `*` and `_` within words or surrounded by spaces.
+    /// This is not a full markdown parser: other constructs are left as is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::schema::Description;
+    /// let plain = |markdown: &str| Description::markdown(markdown).as_plain().into_owned();
+    /// assert_eq!(plain("Path of the **remote** file"), "Path of the remote file");
+    /// assert_eq!(plain("Use `cmd_file` to _upload_ a file"), "Use cmd_file to upload a file");
+    /// assert_eq!(plain("See [the docs](https://example.com/docs)."), "See the docs.");
+    /// assert_eq!(plain(r"Mode as in \*chmod\* [octal]"), "Mode as in *chmod* [octal]");
+    /// assert_eq!(plain("Files matching `*.tf` or `__init__.py`"), "Files matching *.tf or __init__.py");
+    /// assert_eq!(plain("Timeout in seconds: 2 * 60 by default, see *snake_case*"), "Timeout in seconds: 2 * 60 by default, see snake_case");
+    ///
+    /// // Plain descriptions are left untouched
+    /// assert_eq!(Description::plain("**not** `markdown`").as_plain(), "**not** `markdown`");
+    /// ```
+    pub fn as_plain(&self) -> Cow<'_, str> {
+        match self.kind {
+            StringKind::Plain => Cow::Borrowed(&self.content),
+            StringKind::Markdown => Cow::Owned(strip_markdown(&self.content)),
+        }
+    }
+}
+
+/// Strip basic markdown from `markdown`, see [`Description::as_plain`]
+fn strip_markdown(markdown: &str) -> String {
+    let chars = markdown.chars().collect::<Vec<_>>();
+    // Emphasis is a run of `*` or `_` that is neither within a word, like in `snake_case`,
+    // nor surrounded by spaces, like in `2 * 3`
+    let is_emphasis = |i: usize| {
+        let c = chars[i];
+        let start = chars[..i]
+            .iter()
+            .rposition(|&d| d != c)
+            .map_or(0, |j| j + 1);
+        let end = chars[i..]
+            .iter()
+            .position(|&d| d != c)
+            .map_or(chars.len(), |j| i + j);
+        let before = start.checked_sub(1).and_then(|j| chars.get(j));
+        let after = chars.get(end);
+        let is_text = |c: Option<&char>| c.is_some_and(|c| !c.is_whitespace());
+        let is_alphanumeric = |c: Option<&char>| c.is_some_and(|c| c.is_alphanumeric());
+        (is_text(before) || is_text(after)) && !(is_alphanumeric(before) && is_alphanumeric(after))
+    };
+    // Position of the closing bracket and of the closing parenthesis of a link, eg: `[text](url)`
+    let link_tail = |i: usize| {
+        let bracket = i + chars[i..].iter().position(|&c| c == ']')?;
+        if chars.get(bracket + 1) != Some(&'(') {
+            return None;
+        }
+        let paren = bracket + chars[bracket..].iter().position(|&c| c == ')')?;
+        Some((bracket, paren))
+    };
+
+    let mut plain = String::with_capacity(markdown.len());
+    let mut tail = None;
+    let mut escaped = false;
+    let mut code = false;
+    for (i, &c) in chars.iter().enumerate() {
+        if let Some((bracket, paren)) = tail {
+            if i == paren {
+                tail = None;
+            }
+            if i >= bracket {
+                continue;
+            }
+        }
+        if escaped {
+            escaped = false;
+            plain.push(c);
+            continue;
+        }
+        if code {
+            match c {
+                '`' => code = false,
+                _ => plain.push(c),
+            }
+            continue;
+        }
+        match c {
+            '\\' if chars.get(i + 1).is_some_and(char::is_ascii_punctuation) => escaped = true,
+            '`' => code = true,
+            '*' | '_' if is_emphasis(i) => (),
+            '[' if tail.is_none() => match link_tail(i) {
+                Some(link) => tail = Some(link),
+                None => plain.push(c),
+            },
+            _ => plain.push(c),
+        }
+    }
+    plain
 }
 
 impl<T> From<T> for Description
@@ -247,6 +385,11 @@ pub enum AttributeType {
     String,
     /// Number (int or float)
     Number,
+    /// Integer
+    ///
+    /// Serialized as a number, as Terraform does not distinguish integers,
+    /// but values can be checked with [`AttributeType::validate_number`].
+    Integer,
     /// Boolean
     Bool,
     /// List
@@ -291,7 +434,7 @@ impl Serialize for AttributeType {
         }
         match self {
             AttributeType::String => serializer.serialize_str("string"),
-            AttributeType::Number => serializer.serialize_str("number"),
+            AttributeType::Number | AttributeType::Integer => serializer.serialize_str("number"),
             AttributeType::Bool => serializer.serialize_str("bool"),
             AttributeType::List(attr) => ("list", attr).serialize(serializer),
             AttributeType::Set(attr) => ("set", attr).serialize(serializer),
@@ -315,6 +458,158 @@ impl Serialize for AttributeType {
     }
 }
 
+impl AttributeType {
+    /// Check that the numbers of a value are integers wherever the type requires an integer
+    ///
+    /// # Arguments
+    ///
+    /// * `diags` - Diagnostics where non-integer numbers are reported
+    /// * `path` - Path of the value
+    /// * `value` - Value to check
+    ///
+    /// # Remarks
+    ///
+    /// Returns [`None`] iff there is an error reported in diagnostics.
+    /// Null and unknown values are valid.
+    /// Numbers encoded as strings are checked as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::schema::AttributeType;
+    /// # use tf_provider::value::{Number, ValueAny};
+    /// # use tf_provider::{AttributePath, Diagnostics};
+    /// // Integers are still numbers for Terraform
+    /// assert_eq!(AttributeType::Integer.to_string(), r#""number""#);
+    ///
+    /// let mut diags = Diagnostics::default();
+    /// let path = AttributePath::new("mode");
+    ///
+    /// let mode = ValueAny::Number(0o644.into());
+    /// assert_eq!(AttributeType::Integer.validate_number(&mut diags, path.clone(), &mode), Some(()));
+    /// assert_eq!(AttributeType::Integer.validate_number(&mut diags, path.clone(), &ValueAny::Unknown), Some(()));
+    ///
+    /// let mode = ValueAny::Number(Number::Decimal("420.5".into()));
+    /// assert_eq!(AttributeType::Number.validate_number(&mut diags, path.clone(), &mode), Some(()));
+    /// assert_eq!(AttributeType::Integer.validate_number(&mut diags, path.clone(), &mode), None);
+    /// assert_eq!(diags.errors[0].attribute, path);
+    ///
+    /// // Nested integers
+    /// let mut diags = Diagnostics::default();
+    /// let modes = ValueAny::List(vec![ValueAny::Number(0o600.into()), mode]);
+    /// let attr_type = AttributeType::List(AttributeType::Integer.into());
+    /// assert_eq!(attr_type.validate_number(&mut diags, AttributePath::new("modes"), &modes), None);
+    /// assert_eq!(diags.errors[0].attribute, AttributePath::new("modes").index(1));
+    ///
+    /// // Decimals beyond the `f64` precision are encoded as strings
+    /// # use tf_provider::RawValue;
+    /// let mut diags = Diagnostics::default();
+    /// let mode = ValueAny::Number(Number::Decimal("420.00000000000000000001".into()));
+    /// let mode: ValueAny = RawValue::serialize(&mut diags, &mode).unwrap().deserialize(&mut diags).unwrap();
+    /// assert_eq!(AttributeType::Integer.validate_number(&mut diags, path.clone(), &mode), None);
+    ///
+    /// let mut diags = Diagnostics::default();
+    /// let mode = ValueAny::String("420.5".into());
+    /// assert_eq!(AttributeType::Integer.validate_number(&mut diags, path.clone(), &mode), None);
+    /// let mode = ValueAny::String("420".into());
+    /// assert_eq!(AttributeType::Integer.validate_number(&mut diags, path.clone(), &mode), Some(()));
+    /// assert_eq!(diags.error_count(), 1);
+    /// ```
+    pub fn validate_number(
+        &self,
+        diags: &mut Diagnostics,
+        path: AttributePath,
+        value: &ValueAny,
+    ) -> Option<()> {
+        let errors = diags.error_count();
+        self.validate_number_at(diags, path, value);
+        if diags.error_count() == errors {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn validate_number_at(&self, diags: &mut Diagnostics, path: AttributePath, value: &ValueAny) {
+        let validate_attributes = |diags: &mut Diagnostics,
+                                   attrs: &HashMap<String, Attribute>,
+                                   path: AttributePath,
+                                   value: &ValueAny| {
+            if let ValueAny::Map(map) = value {
+                for (name, attr) in attrs {
+                    if let Some(value) = map.get(name) {
+                        let path = path.clone().attribute(name.clone());
+                        attr.attr_type.validate_number_at(diags, path, value);
+                    }
+                }
+            }
+        };
+        match (self, value) {
+            (AttributeType::Integer, ValueAny::Number(Number::Decimal(number))) => diags.error(
+                "Invalid integer",
+                format!("`{number}` is not an integer"),
+                path,
+            ),
+            (AttributeType::Integer, ValueAny::String(number))
+                if matches!(Number::parse(number), Some(Number::Decimal(_))) =>
+            {
+                diags.error(
+                    "Invalid integer",
+                    format!("`{number}` is not an integer"),
+                    path,
+                )
+            }
+            (AttributeType::List(attr_type), ValueAny::List(values)) => {
+                for (i, value) in values.iter().enumerate() {
+                    attr_type.validate_number_at(diags, path.clone().index(i as i64), value);
+                }
+            }
+            (AttributeType::Set(attr_type), ValueAny::List(values)) => {
+                for value in values {
+                    attr_type.validate_number_at(diags, path.clone(), value);
+                }
+            }
+            (AttributeType::Map(attr_type), ValueAny::Map(values)) => {
+                for (key, value) in values {
+                    attr_type.validate_number_at(diags, path.clone().key(key.clone()), value);
+                }
+            }
+            (AttributeType::Object(attr_types), ValueAny::Map(values)) => {
+                for (name, attr_type) in attr_types {
+                    if let Some(value) = values.get(name) {
+                        let path = path.clone().attribute(name.clone());
+                        attr_type.validate_number_at(diags, path, value);
+                    }
+                }
+            }
+            (AttributeType::Tuple(attr_types), ValueAny::List(values)) => {
+                for (i, (attr_type, value)) in attr_types.iter().zip(values).enumerate() {
+                    attr_type.validate_number_at(diags, path.clone().index(i as i64), value);
+                }
+            }
+            (AttributeType::AttributeSingle(attrs), value) => {
+                validate_attributes(diags, attrs, path, value)
+            }
+            (AttributeType::AttributeList(attrs), ValueAny::List(values)) => {
+                for (i, value) in values.iter().enumerate() {
+                    validate_attributes(diags, attrs, path.clone().index(i as i64), value);
+                }
+            }
+            (AttributeType::AttributeSet(attrs), ValueAny::List(values)) => {
+                for value in values {
+                    validate_attributes(diags, attrs, path.clone(), value);
+                }
+            }
+            (AttributeType::AttributeMap(attrs), ValueAny::Map(values)) => {
+                for (key, value) in values {
+                    validate_attributes(diags, attrs, path.clone().key(key.clone()), value);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
 impl Display for AttributeType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         return f.write_str(
@@ -352,6 +647,13 @@ pub struct Attribute {
     pub sensitive: bool,
     /// Is the attribute deprecated
     pub deprecated: bool,
+    /// Default value of the attribute
+    ///
+    /// The default is applied to the proposed state when planning the creation of a resource,
+    /// if the attribute is not set in the configuration (see [`Schema::apply_defaults`]).
+    /// Only [`AttributeConstraint::OptionalComputed`] attributes can have a default,
+    /// as Terraform rejects planned values that differ from the configuration for non-computed attributes.
+    pub default: Option<ValueAny>,
 }
 
 impl Default for Attribute {
@@ -362,6 +664,7 @@ impl Default for Attribute {
             constraint: AttributeConstraint::OptionalComputed,
             sensitive: false,
             deprecated: false,
+            default: None,
         }
     }
 }
@@ -375,6 +678,435 @@ pub struct Schema {
     pub block: Block,
 }
 
+impl Schema {
+    /// Validate the schema
+    ///
+    /// The following rules are checked:
+    /// - within each block, attributes and nested blocks share the same namespace:
+    ///   an attribute and a nested block cannot have the same name,
+    /// - only [`AttributeConstraint::OptionalComputed`] attributes can have a default value,
+    /// - nested attributes must declare at least one attribute.
+    ///
+    /// # Arguments
+    ///
+    /// * `diags` - Diagnostics where validation errors are reported
+    ///
+    /// # Remarks
+    ///
+    /// Returns [`None`] iff there is an error reported in diagnostics
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::schema::{Attribute, AttributeConstraint, AttributeType, Block, NestedBlock, Schema};
+    /// # use tf_provider::value::ValueAny;
+    /// # use tf_provider::{map, AttributePath, Diagnostics};
+    /// let schema = Schema {
+    ///     version: 1,
+    ///     block: Block {
+    ///         blocks: map! {
+    ///             "connection" => NestedBlock::Single(Block {
+    ///                 attributes: map! { "host" => Attribute::default() },
+    ///                 blocks: map! { "host" => NestedBlock::Optional(Block::default()) },
+    ///                 ..Default::default()
+    ///             }),
+    ///         },
+    ///         ..Default::default()
+    ///     },
+    /// };
+    ///
+    /// let mut diags = Diagnostics::default();
+    /// assert_eq!(schema.validate(&mut diags), None);
+    /// assert_eq!(diags.errors.len(), 1);
+    /// assert_eq!(diags.errors[0].attribute, AttributePath::new("connection").attribute("host"));
+    ///
+    /// let schema = |attributes| Schema {
+    ///     version: 1,
+    ///     block: Block { attributes, ..Default::default() },
+    /// };
+    /// let path = Attribute {
+    ///     attr_type: AttributeType::String,
+    ///     constraint: AttributeConstraint::Required,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// // Required attribute with a default
+    /// let mut diags = Diagnostics::default();
+    /// let invalid = schema(map! {
+    ///     "path" => Attribute { default: Some(ValueAny::String("/tmp".into())), ..path.clone() },
+    /// });
+    /// assert_eq!(invalid.validate(&mut diags), None);
+    /// assert_eq!(diags.errors[0].attribute, AttributePath::new("path"));
+    ///
+    /// // Nested attributes without any attribute
+    /// let mut diags = Diagnostics::default();
+    /// let invalid = schema(map! {
+    ///     "connect" => Attribute { attr_type: AttributeType::AttributeSingle(map! {}), ..Default::default() },
+    /// });
+    /// assert_eq!(invalid.validate(&mut diags), None);
+    /// assert_eq!(diags.errors[0].attribute, AttributePath::new("connect"));
+    ///
+    /// // Valid schema
+    /// let mut diags = Diagnostics::default();
+    /// let valid = schema(map! {
+    ///     "path" => path.clone(),
+    ///     "mode" => Attribute { default: Some(ValueAny::String("0644".into())), ..Default::default() },
+    ///     "connect" => Attribute { attr_type: AttributeType::AttributeSingle(map! { "host" => path }), ..Default::default() },
+    /// });
+    /// assert_eq!(valid.validate(&mut diags), Some(()));
+    /// assert!(!diags.has_errors());
+    /// ```
+    pub fn validate(&self, diags: &mut Diagnostics) -> Option<()> {
+        let errors = diags.errors.len();
+        self.block.validate(diags, AttributePath::root());
+        if diags.errors.len() == errors {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    /// Build a value with the shape of the schema, where all computed attributes are unknown
+    ///
+    /// Non-computed attributes are null, single and group blocks are recursively built,
+    /// and other nested blocks are empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `diags` - Diagnostics where serialization errors are reported
+    ///
+    /// # Remarks
+    ///
+    /// Returns [`None`] iff there is an error reported in diagnostics
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::schema::{Attribute, AttributeConstraint, AttributeType, Block, NestedBlock, Schema};
+    /// # use tf_provider::value::ValueAny;
+    /// # use tf_provider::{map, Diagnostics};
+    /// let computed = |attr_type| Attribute {
+    ///     attr_type,
+    ///     constraint: AttributeConstraint::Computed,
+    ///     ..Default::default()
+    /// };
+    /// let required = Attribute {
+    ///     attr_type: AttributeType::String,
+    ///     constraint: AttributeConstraint::Required,
+    ///     ..Default::default()
+    /// };
+    /// let schema = Schema {
+    ///     version: 1,
+    ///     block: Block {
+    ///         attributes: map! {
+    ///             "path" => required.clone(),
+    ///             "id" => computed(AttributeType::String),
+    ///             "size" => computed(AttributeType::Number),
+    ///         },
+    ///         blocks: map! {
+    ///             "connect" => NestedBlock::Single(Block {
+    ///                 attributes: map! { "host" => required.clone(), "port" => Attribute::default() },
+    ///                 ..Default::default()
+    ///             }),
+    ///             "env" => NestedBlock::List(Block::default()),
+    ///         },
+    ///         ..Default::default()
+    ///     },
+    /// };
+    ///
+    /// let mut diags = Diagnostics::default();
+    /// let value: ValueAny = schema.unknown_value(&mut diags).unwrap().deserialize(&mut diags).unwrap();
+    /// let ValueAny::Map(value) = value else { panic!("expected a map") };
+    /// assert_eq!(value["path"], ValueAny::Null);
+    /// assert_eq!(value["id"], ValueAny::Unknown);
+    /// assert_eq!(value["size"], ValueAny::Unknown);
+    /// assert_eq!(value["connect"], ValueAny::Map([
+    ///     ("host".to_owned(), ValueAny::Null),
+    ///     ("port".to_owned(), ValueAny::Unknown),
+    /// ].into()));
+    /// assert_eq!(value["env"], ValueAny::List(vec![]));
+    /// ```
+    pub fn unknown_value(&self, diags: &mut Diagnostics) -> Option<RawValue> {
+        RawValue::serialize(diags, &self.block.unknown_value())
+    }
+
+    /// Apply the default values of the attributes to a state
+    ///
+    /// Every null attribute with a default value is replaced by its default.
+    /// Nested blocks and nested attributes are recursively processed.
+    ///
+    /// # Arguments
+    ///
+    /// * `diags` - Diagnostics where serialization errors are reported
+    /// * `state` - State where the defaults are applied
+    ///
+    /// # Remarks
+    ///
+    /// Returns [`None`] iff there is an error reported in diagnostics.
+    /// The protocol has no way to advertise defaults to Terraform,
+    /// so they are applied to the proposed state before calling [`plan_create`](crate::DynamicResource::plan_create).
+    /// If no default is applied, `state` is returned untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde_json::json;
+    /// # use tf_provider::schema::{Attribute, AttributeConstraint, AttributeType, Block, Schema};
+    /// # use tf_provider::value::{Number, ValueAny};
+    /// # use tf_provider::{map, Diagnostics, RawValue};
+    /// let schema = Schema {
+    ///     version: 1,
+    ///     block: Block {
+    ///         attributes: map! {
+    ///             "payload" => Attribute::default(),
+    ///             "path" => Attribute {
+    ///                 attr_type: AttributeType::String,
+    ///                 constraint: AttributeConstraint::Required,
+    ///                 ..Default::default()
+    ///             },
+    ///             "mode" => Attribute {
+    ///                 attr_type: AttributeType::String,
+    ///                 default: Some(ValueAny::String("0666".into())),
+    ///                 ..Default::default()
+    ///             },
+    ///             "overwrite" => Attribute {
+    ///                 attr_type: AttributeType::Bool,
+    ///                 default: Some(ValueAny::Bool(false)),
+    ///                 ..Default::default()
+    ///             },
+    ///         },
+    ///         ..Default::default()
+    ///     },
+    /// };
+    ///
+    /// let mut diags = Diagnostics::default();
+    /// let proposed = |mode| ValueAny::Map([
+    ///     ("path".to_owned(), ValueAny::String("/tmp/file".into())),
+    ///     ("mode".to_owned(), mode),
+    ///     ("overwrite".to_owned(), ValueAny::Bool(true)),
+    ///     ("payload".to_owned(), ValueAny::Dynamic(json!(["list", "number"]), Box::new(ValueAny::List(vec![
+    ///         ValueAny::Number(Number::Int(1 << 100)),
+    ///         ValueAny::Number(Number::Decimal("0.10000000000000000000001".into())),
+    ///     ])))),
+    /// ].into());
+    /// let raw = RawValue::serialize(&mut diags, &proposed(ValueAny::Null)).unwrap();
+    ///
+    /// let planned = schema.apply_defaults(&mut diags, raw).unwrap();
+    /// let planned: ValueAny = planned.deserialize(&mut diags).unwrap();
+    /// assert_eq!(planned, proposed(ValueAny::String("0666".into())));
+    ///
+    /// // The state is untouched if no default is applied
+    /// let raw = RawValue::serialize(&mut diags, &proposed(ValueAny::String("0644".into()))).unwrap();
+    /// assert_eq!(schema.apply_defaults(&mut diags, raw.clone()), Some(raw.clone()));
+    ///
+    /// let schema = Schema { version: 1, block: Block::default() };
+    /// let raw = RawValue::MessagePack(vec![0x81, 0xa4, b'p', b'a', b't', b'h', 0xc0]);
+    /// assert_eq!(schema.apply_defaults(&mut diags, raw.clone()), Some(raw));
+    /// assert!(!diags.has_errors());
+    /// ```
+    pub fn apply_defaults(&self, diags: &mut Diagnostics, state: RawValue) -> Option<RawValue> {
+        if state.is_null() || !self.block.has_defaults() {
+            return Some(state);
+        }
+        let mut value = state.deserialize::<ValueAny>(diags)?;
+        if self.block.apply_defaults(&mut value) {
+            RawValue::serialize(diags, &value)
+        } else {
+            Some(state)
+        }
+    }
+}
+
+/// Check if any of `attributes`, or of their nested attributes, has a default value
+fn has_attribute_defaults(attributes: &HashMap<String, Attribute>) -> bool {
+    attributes.values().any(|attr| {
+        attr.default.is_some()
+            || match &attr.attr_type {
+                AttributeType::AttributeSingle(attrs)
+                | AttributeType::AttributeList(attrs)
+                | AttributeType::AttributeSet(attrs)
+                | AttributeType::AttributeMap(attrs) => has_attribute_defaults(attrs),
+                _ => false,
+            }
+    })
+}
+
+/// Apply the defaults of `attributes` to an object value, and return whether any default was applied
+fn apply_attribute_defaults(attributes: &HashMap<String, Attribute>, value: &mut ValueAny) -> bool {
+    let ValueAny::Map(map) = value else {
+        return false;
+    };
+    let mut applied = false;
+    for (name, attr) in attributes {
+        let Some(value) = map.get_mut(name) else {
+            continue;
+        };
+        if let (ValueAny::Null, Some(default)) = (&value, &attr.default) {
+            *value = default.clone();
+            applied = true;
+        }
+        match (&attr.attr_type, value) {
+            (AttributeType::AttributeSingle(attrs), value) => {
+                applied |= apply_attribute_defaults(attrs, value);
+            }
+            (
+                AttributeType::AttributeList(attrs) | AttributeType::AttributeSet(attrs),
+                ValueAny::List(values),
+            ) => {
+                for value in values {
+                    applied |= apply_attribute_defaults(attrs, value);
+                }
+            }
+            (AttributeType::AttributeMap(attrs), ValueAny::Map(values)) => {
+                for value in values.values_mut() {
+                    applied |= apply_attribute_defaults(attrs, value);
+                }
+            }
+            _ => (),
+        }
+    }
+    applied
+}
+
+/// Report invalid attributes: defaults on non optional and computed attributes, and empty nested attributes
+fn validate_attributes(
+    attributes: &HashMap<String, Attribute>,
+    diags: &mut Diagnostics,
+    path: &AttributePath,
+) {
+    let mut attributes = attributes.iter().collect::<Vec<_>>();
+    attributes.sort_by_key(|(name, _)| *name);
+    for (name, attr) in attributes {
+        let path = path.clone().attribute(name.clone());
+        if attr.default.is_some() && attr.constraint != AttributeConstraint::OptionalComputed {
+            diags.error(
+                "Invalid schema",
+                format!("`{name}` has a default value, but is not optional and computed"),
+                path.clone(),
+            );
+        }
+        match &attr.attr_type {
+            AttributeType::AttributeSingle(attrs)
+            | AttributeType::AttributeList(attrs)
+            | AttributeType::AttributeSet(attrs)
+            | AttributeType::AttributeMap(attrs) => {
+                if attrs.is_empty() {
+                    diags.error(
+                        "Invalid schema",
+                        format!("`{name}` has nested attributes, but none is declared"),
+                        path.clone(),
+                    );
+                }
+                validate_attributes(attrs, diags, &path)
+            }
+            _ => (),
+        }
+    }
+}
+
+impl Block {
+    fn has_defaults(&self) -> bool {
+        has_attribute_defaults(&self.attributes)
+            || self.blocks.values().any(|nested_block| match nested_block {
+                NestedBlock::Single(block)
+                | NestedBlock::Group(block)
+                | NestedBlock::List(block)
+                | NestedBlock::Set(block)
+                | NestedBlock::Optional(block)
+                | NestedBlock::Map(block) => block.has_defaults(),
+            })
+    }
+
+    fn apply_defaults(&self, value: &mut ValueAny) -> bool {
+        let mut applied = apply_attribute_defaults(&self.attributes, value);
+        let ValueAny::Map(map) = value else {
+            return applied;
+        };
+        for (name, nested_block) in &self.blocks {
+            let Some(value) = map.get_mut(name) else {
+                continue;
+            };
+            match (nested_block, value) {
+                (NestedBlock::Single(block) | NestedBlock::Group(block), value) => {
+                    applied |= block.apply_defaults(value);
+                }
+                (
+                    NestedBlock::List(block)
+                    | NestedBlock::Set(block)
+                    | NestedBlock::Optional(block),
+                    ValueAny::List(values),
+                ) => {
+                    for value in values {
+                        applied |= block.apply_defaults(value);
+                    }
+                }
+                (NestedBlock::Map(block), ValueAny::Map(values)) => {
+                    for value in values.values_mut() {
+                        applied |= block.apply_defaults(value);
+                    }
+                }
+                _ => (),
+            }
+        }
+        applied
+    }
+
+    fn unknown_value(&self) -> ValueAny {
+        let attributes = self.attributes.iter().map(|(name, attr)| {
+            let value = match attr.constraint {
+                AttributeConstraint::Computed | AttributeConstraint::OptionalComputed => {
+                    ValueAny::Unknown
+                }
+                AttributeConstraint::Optional | AttributeConstraint::Required => ValueAny::Null,
+            };
+            (name.clone(), value)
+        });
+        let blocks = self.blocks.iter().map(|(name, nested_block)| {
+            let value = match nested_block {
+                NestedBlock::Single(block) | NestedBlock::Group(block) => block.unknown_value(),
+                NestedBlock::List(_) | NestedBlock::Set(_) | NestedBlock::Optional(_) => {
+                    ValueAny::List(Vec::new())
+                }
+                NestedBlock::Map(_) => ValueAny::Map(BTreeMap::new()),
+            };
+            (name.clone(), value)
+        });
+        ValueAny::Map(attributes.chain(blocks).collect())
+    }
+
+    fn validate(&self, diags: &mut Diagnostics, path: AttributePath) {
+        validate_attributes(&self.attributes, diags, &path);
+
+        let mut names = self
+            .attributes
+            .keys()
+            .filter(|name| self.blocks.contains_key(*name))
+            .collect::<Vec<_>>();
+        names.sort();
+        for name in names {
+            diags.error(
+                "Invalid schema",
+                format!("`{name}` is declared both as an attribute and as a nested block"),
+                path.clone().attribute(name.clone()),
+            );
+        }
+
+        let mut blocks = self.blocks.iter().collect::<Vec<_>>();
+        blocks.sort_by_key(|(name, _)| *name);
+        for (name, nested_block) in blocks {
+            let block = match nested_block {
+                NestedBlock::Single(block)
+                | NestedBlock::List(block)
+                | NestedBlock::Set(block)
+                | NestedBlock::Map(block)
+                | NestedBlock::Group(block)
+                | NestedBlock::Optional(block) => block,
+            };
+            block.validate(diags, path.clone().attribute(name.clone()));
+        }
+    }
+}
+
 impl From<&Schema> for tfplugin6::Schema {
     fn from(value: &Schema) -> Self {
         Self {
@@ -17,21 +17,32 @@
 //! [`Server`] module
 
 use std::collections::HashMap;
+use std::convert::Infallible;
 use std::env;
 use std::fs::File;
+use std::future::Future;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::pin::pin;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use anyhow::{anyhow, Result};
 use base64::Engine;
+use futures::future::{select, Either};
 use futures::TryFutureExt;
 use rcgen::{BasicConstraints, IsCa};
 use time::ext::NumericalDuration;
+use tokio::sync::{oneshot, Semaphore};
 use tokio::try_join;
 use tokio_stream::wrappers::TcpListenerStream;
 use tokio_util::sync::CancellationToken;
+use tonic::body::BoxBody;
+use tonic::codegen::{http, Service};
+use tonic::server::NamedService;
 use tonic::transport::{Identity, ServerTlsConfig};
 use tower_http::trace::TraceLayer;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 
 use crate::function::DynamicFunction;
 use crate::plugin::grpc_broker_server::GrpcBrokerServer;
@@ -41,23 +52,31 @@ use crate::schema::FunctionSchema;
 use crate::tfplugin6::provider_server::ProviderServer;
 
 use crate::data_source::DynamicDataSource;
+use crate::metrics::Metrics;
 use crate::plugin::GrpcIo;
 use crate::provider::DynamicProvider;
 use crate::resource::DynamicResource;
+use crate::signal;
 use crate::{schema::Schema, Diagnostics};
 
 const CORE_PROTOCOL_VERSION: u8 = 1;
 
+/// Number of permits of the in-flight semaphore: each in-flight operation holds a single permit
+const IN_FLIGHT_PERMITS: u32 = 1 << 28;
+
 /// TF provider server
 pub(crate) struct Server {
     pub(crate) provider: Box<dyn DynamicProvider>,
     pub(crate) io: GrpcIo,
     pub(crate) cancellation_token: CancellationToken,
+    pub(crate) in_flight: Semaphore,
+    pub(crate) grace_period: Duration,
+    pub(crate) metrics: Metrics,
 
     pub(crate) init_diags: Diagnostics,
     pub(crate) schema: Option<Schema>,
     pub(crate) meta_schema: Option<Schema>,
-    pub(crate) resources: HashMap<String, (Box<dyn DynamicResource>, Schema)>,
+    pub(crate) resources: HashMap<String, (Arc<dyn DynamicResource>, Schema)>,
     pub(crate) data_sources: HashMap<String, (Box<dyn DynamicDataSource>, Schema)>,
     pub(crate) functions: HashMap<String, (Box<dyn DynamicFunction>, FunctionSchema)>,
 }
@@ -72,16 +91,41 @@ impl Server {
         };
         let schema = provider.schema(&mut diags).or_else(&mut set_error);
         let meta_schema = provider.meta_schema(&mut diags).or_else(&mut set_error);
-        let resources = provider
+        let mut resources: HashMap<_, (Arc<dyn DynamicResource>, Schema)> = provider
             .get_resources(&mut diags)
             .unwrap_or_default()
             .into_iter()
             .filter_map(|(name, resource)| {
                 let schema = resource.schema(&mut diags).or_else(&mut set_error)?;
-                Some((format!("{}_{}", provider_name, name), (resource, schema)))
+                Some((format!("{}_{}", provider_name, name), (resource.into(), schema)))
             })
             .collect();
-        let data_sources = provider
+        let mut aliases = provider
+            .get_resource_aliases(&mut diags)
+            .unwrap_or_default()
+            .into_iter()
+            .collect::<Vec<_>>();
+        aliases.sort();
+        for (alias, name) in aliases {
+            let alias = format!("{}_{}", provider_name, alias);
+            let name = format!("{}_{}", provider_name, name);
+            if resources.contains_key(&alias) {
+                diags.root_error(
+                    "Invalid resource alias",
+                    format!("Alias `{alias}` conflicts with an existing resource"),
+                );
+            } else if let Some((resource, schema)) = resources.get(&name) {
+                let mut schema = schema.clone();
+                schema.block.deprecated = true;
+                resources.insert(alias, (resource.clone(), schema));
+            } else {
+                diags.root_error(
+                    "Invalid resource alias",
+                    format!("Alias `{alias}` refers to the unknown resource `{name}`"),
+                );
+            }
+        }
+        let data_sources: HashMap<_, (Box<dyn DynamicDataSource>, Schema)> = provider
             .get_data_sources(&mut diags)
             .unwrap_or_default()
             .into_iter()
@@ -107,10 +151,37 @@ impl Server {
             diags.internal_error()
         }
 
+        let grace_period = provider.stop_grace_period();
+
+        let mut schemas = [("provider", &schema), ("provider meta", &meta_schema)]
+            .into_iter()
+            .filter_map(|(kind, schema)| Some((format!("the {kind}"), schema.as_ref()?)))
+            .collect::<Vec<_>>();
+        let mut named = resources
+            .iter()
+            .map(|(name, (_, schema))| (format!("resource `{name}`"), schema))
+            .chain(
+                data_sources
+                    .iter()
+                    .map(|(name, (_, schema))| (format!("data source `{name}`"), schema)),
+            )
+            .collect::<Vec<_>>();
+        // Report errors in a deterministic order
+        named.sort_by(|(a, _), (b, _)| a.cmp(b));
+        schemas.extend(named);
+        for (name, schema) in schemas {
+            diags.with_context(&format!("Invalid schema of {name}"), |diags| {
+                schema.validate(diags)
+            });
+        }
+
         Self {
             provider,
             io: Default::default(),
             cancellation_token: Default::default(),
+            in_flight: Semaphore::new(IN_FLIGHT_PERMITS as usize),
+            grace_period,
+            metrics: Default::default(),
             init_diags: diags,
             schema,
             meta_schema,
@@ -132,6 +203,11 @@ impl Server {
             None
         }
     }
+    pub(crate) fn can_move_state(&self) -> bool {
+        self.resources
+            .values()
+            .any(|(resource, _)| resource.can_move_state())
+    }
     pub(crate) fn get_data_source<'a>(
         &'a self,
         diags: &mut Diagnostics,
@@ -156,6 +232,41 @@ impl Server {
             None
         }
     }
+
+    /// Run an operation of the provider, tracking it as in-flight until it completes
+    ///
+    /// Returns [`None`] if the operation has been aborted because it was still running
+    /// after the grace period that follows the stop of the provider.
+    pub(crate) async fn track<F: Future>(&self, operation: F) -> Option<F::Output> {
+        // The semaphore is never closed
+        let _permit = self.in_flight.acquire().await.ok()?;
+        let aborted = pin!(async {
+            self.cancellation_token.cancelled().await;
+            tokio::time::sleep(self.grace_period).await;
+        });
+        match select(pin!(operation), aborted).await {
+            Either::Left((output, _)) => Some(output),
+            Either::Right(_) => None,
+        }
+    }
+
+    /// Report that an operation has been aborted because the provider is stopping
+    pub(crate) fn aborted<T>(&self, diags: &mut Diagnostics) -> Option<T> {
+        diags.root_error(
+            "Operation cancelled",
+            "The provider has been stopped before the operation could complete.",
+        );
+        None
+    }
+
+    /// Wait for the in-flight operations to complete
+    ///
+    /// Operations still running after the grace period are aborted, and release their permit as soon as they are dropped.
+    /// New operations wait until the draining is over.
+    pub(crate) async fn drain(&self) {
+        // The semaphore is never closed
+        _ = self.in_flight.acquire_many(IN_FLIGHT_PERMITS).await;
+    }
 }
 
 /// Serve the provider
@@ -165,13 +276,65 @@ impl Server {
 /// * `name` - Name of the provider to serve
 /// * `provider` - Provider to be served
 ///
+/// # Signals
+///
+/// On `SIGTERM` or `SIGINT`, the server stops accepting new requests,
+/// waits for the in-flight requests to complete, and returns,
+/// as if Terraform had requested the provider to stop.
+/// Requests still running after [`DynamicProvider::stop_grace_period`] are cancelled.
+///
+/// ```
+/// # use tf_provider::schema::{Block, Schema};
+/// # use tf_provider::{serve_with_ready, Diagnostics, DynamicProvider};
+/// # struct Empty;
+/// # impl DynamicProvider for Empty {
+/// #     fn schema(&self, _diags: &mut Diagnostics) -> Option<Schema> {
+/// #         Some(Schema { version: 1, block: Block::default() })
+/// #     }
+/// # }
+/// # #[cfg(unix)]
+/// tokio::runtime::Builder::new_current_thread()
+///     .enable_all()
+///     .build()
+///     .unwrap()
+///     .block_on(async {
+///         let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
+///         let server = tokio::spawn(serve_with_ready("empty", Empty, ready_tx));
+///         ready_rx.await.unwrap();
+///
+///         std::process::Command::new("kill")
+///             .args(["-TERM", &std::process::id().to_string()])
+///             .status()
+///             .unwrap();
+///
+///         assert!(server.await.unwrap().is_ok());
+///     });
+/// ```
+///
+/// # Logging
+///
+/// Logs are written to the file `PLUGIN_LOG_FILE` if set.
+/// If `TF_PROVIDER_LOG` is set, logs are written to stderr unless `PLUGIN_LOG_FILE` is set,
+/// and the entry and exit of every RPC are logged, along with the resource type name and the elapsed time.
+/// `TF_PROVIDER_LOG` selects the format of the logs: either `json`, `pretty`, or plain text.
+/// Logs are never written to stdout, that carries the handshake.
+///
+/// # Metrics
+///
+/// With the `metrics` feature, if `TF_PROVIDER_METRICS_ADDR` is set,
+/// metrics of the RPCs are served in the Prometheus text format over HTTP on this address:
+/// the number of completed RPCs and their cumulated duration per RPC, and the number of RPCs being served.
+/// Metrics are only served when debugging the provider, ie: when the provider is not launched by Terraform
+/// but started on its own and attached with `TF_REATTACH_PROVIDERS`.
+/// Without the `metrics` feature, the RPCs are not recorded at all.
+///
 /// # Cancel Safety
 ///
 /// It is not safe to cancel the output future
 ///
 /// # See Also
 ///
-/// [`serve_dynamic`]
+/// [`serve_dynamic`], [`serve_with_ready`]
 pub async fn serve<U: ToString, V: DynamicProvider>(name: U, provider: V) -> Result<()> {
     serve_dynamic(name.to_string(), Box::new(provider)).await
 }
@@ -188,23 +351,185 @@ pub async fn serve<U: ToString, V: DynamicProvider>(name: U, provider: V) -> Res
 ///
 /// # See Also
 ///
-/// [`serve`]
+/// [`serve`], [`serve_dynamic_with_ready`]
 pub async fn serve_dynamic(name: String, provider: Box<dyn DynamicProvider>) -> Result<()> {
+    serve_impl(name, provider, None).await
+}
+
+/// Serve the provider, and signal when it is ready to accept connections
+///
+/// # Arguments
+///
+/// * `name` - Name of the provider to serve
+/// * `provider` - Provider to be served
+/// * `ready` - Channel receiving the address of the server once the listener is bound and the handshake is printed
+///
+/// # Cancel Safety
+///
+/// It is not safe to cancel the output future
+///
+/// # Examples
+///
+/// ```
+/// # use tf_provider::schema::{Block, Schema};
+/// # use tf_provider::{serve_with_ready, Diagnostics, DynamicProvider};
+/// struct Empty;
+///
+/// impl DynamicProvider for Empty {
+///     fn schema(&self, _diags: &mut Diagnostics) -> Option<Schema> {
+///         Some(Schema { version: 1, block: Block::default() })
+///     }
+/// }
+///
+/// let runtime = tokio::runtime::Builder::new_current_thread()
+///     .enable_all()
+///     .build()
+///     .unwrap();
+/// runtime.block_on(async {
+///     let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
+///     tokio::spawn(serve_with_ready("empty", Empty, ready_tx));
+///
+///     let addr = ready_rx.await.unwrap();
+///     tokio::net::TcpStream::connect(addr).await.unwrap();
+/// });
+/// ```
+///
+/// # See Also
+///
+/// [`serve`], [`serve_dynamic_with_ready`]
+pub async fn serve_with_ready<U: ToString, V: DynamicProvider>(
+    name: U,
+    provider: V,
+    ready: oneshot::Sender<SocketAddr>,
+) -> Result<()> {
+    serve_dynamic_with_ready(name.to_string(), Box::new(provider), ready).await
+}
+
+/// Serve the provider, and signal when it is ready to accept connections
+///
+/// # Arguments
+///
+/// * `name` - Name of the provider to serve
+/// * `provider` - Provider to be served
+/// * `ready` - Channel receiving the address of the server once the listener is bound and the handshake is printed
+///
+/// # Cancel Safety
+///
+/// It is not safe to cancel the output future
+///
+/// # See Also
+///
+/// [`serve_dynamic`], [`serve_with_ready`]
+pub async fn serve_dynamic_with_ready(
+    name: String,
+    provider: Box<dyn DynamicProvider>,
+    ready: oneshot::Sender<SocketAddr>,
+) -> Result<()> {
+    serve_impl(name, provider, Some(ready)).await
+}
+
+/// Build the gRPC service of the provider, without serving it
+///
+/// The service can be mounted on a custom tonic server, for instance to embed the provider in a larger process.
+/// Contrary to [`serve`], no handshake is printed, no TLS is configured,
+/// and the go-plugin services (broker, controller, stdio) are not provided.
+///
+/// # Arguments
+///
+/// * `name` - Name of the provider to serve
+/// * `provider` - Provider to be served
+///
+/// # Examples
+///
+/// ```
+/// # use tf_provider::schema::{Block, Schema};
+/// # use tf_provider::{build_service, Diagnostics, DynamicProvider};
+/// struct Empty;
+///
+/// impl DynamicProvider for Empty {
+///     fn schema(&self, _diags: &mut Diagnostics) -> Option<Schema> {
+///         Some(Schema { version: 1, block: Block::default() })
+///     }
+/// }
+///
+/// let runtime = tokio::runtime::Builder::new_current_thread()
+///     .enable_all()
+///     .build()
+///     .unwrap();
+/// runtime.block_on(async {
+///     let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+///     let addr = listener.local_addr().unwrap();
+///     tokio::spawn(
+///         tonic::transport::Server::builder()
+///             .add_service(build_service("empty", Empty))
+///             .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+///     );
+///
+///     tokio::net::TcpStream::connect(addr).await.unwrap();
+/// });
+/// ```
+///
+/// # See Also
+///
+/// [`build_service_dynamic`], [`serve`]
+pub fn build_service<U: ToString, V: DynamicProvider>(
+    name: U,
+    provider: V,
+) -> impl Service<
+    http::Request<BoxBody>,
+    Response = http::Response<BoxBody>,
+    Error = Infallible,
+    Future = impl Send + 'static,
+> + NamedService
+       + Clone
+       + Send
+       + 'static {
+    build_service_dynamic(name.to_string(), Box::new(provider))
+}
+
+/// Build the gRPC service of the provider, without serving it
+///
+/// # Arguments
+///
+/// * `name` - Name of the provider to serve
+/// * `provider` - Provider to be served
+///
+/// # See Also
+///
+/// [`build_service`], [`serve_dynamic`]
+pub fn build_service_dynamic(
+    name: String,
+    provider: Box<dyn DynamicProvider>,
+) -> impl Service<
+    http::Request<BoxBody>,
+    Response = http::Response<BoxBody>,
+    Error = Infallible,
+    Future = impl Send + 'static,
+> + NamedService
+       + Clone
+       + Send
+       + 'static {
+    ProviderServer::new(Arc::new(Server::new(name, provider)))
+}
+
+async fn serve_impl(
+    name: String,
+    provider: Box<dyn DynamicProvider>,
+    ready: Option<oneshot::Sender<SocketAddr>>,
+) -> Result<()> {
     let server = Arc::new(Server::new(name, provider));
     let addrs = SockAddrIter::new()?;
     let (tcp_stream, endpoint) = listen(addrs)?;
 
-    if let Ok(path) = env::var("PLUGIN_LOG_FILE") {
-        let log_file = File::create(path)?;
-        tracing_subscriber::fmt()
-            .with_max_level(tracing::Level::TRACE)
-            .with_ansi(false)
-            .with_writer(Mutex::new(log_file))
-            .init();
-    }
+    init_logging()?;
 
     let tls_config = TlsConfig::new()?;
 
+    #[cfg(feature = "metrics")]
+    let metrics = crate::metrics::serve_from_env(&server).await?;
+    #[cfg(not(feature = "metrics"))]
+    let metrics = async { Ok(()) };
+
     let serve = tls_config
         .builder()?
         .layer(TraceLayer::new_for_grpc())
@@ -212,26 +537,86 @@ pub async fn serve_dynamic(name: String, provider: Box<dyn DynamicProvider>) ->
         .add_service(GrpcControllerServer::new(server.clone()))
         .add_service(GrpcStdioServer::new(server.clone()))
         .add_service(ProviderServer::new(server.clone()))
-        .serve_with_incoming_shutdown(tcp_stream, server.cancellation_token.cancelled());
+        .serve_with_incoming_shutdown(tcp_stream, shutdown(&server));
 
-    async fn info(endpoint: &str, der: &[u8]) -> Result<()> {
+    async fn info(
+        endpoint: SocketAddr,
+        der: &[u8],
+        ready: Option<oneshot::Sender<SocketAddr>>,
+    ) -> Result<()> {
         println!(
             "{}|6|tcp|{}|grpc|{}",
             CORE_PROTOCOL_VERSION,
             endpoint,
             base64::engine::general_purpose::STANDARD_NO_PAD.encode(der),
         );
+        if let Some(ready) = ready {
+            // The receiver may have been dropped if nobody is waiting for the signal
+            _ = ready.send(endpoint);
+        }
         Ok(())
     }
 
     try_join!(
         serve.map_err(|e| anyhow!(e)),
-        info(&endpoint, tls_config.cert.as_slice()),
+        info(endpoint, tls_config.cert.as_slice(), ready),
+        metrics,
     )?;
 
     Ok(())
 }
 
+/// Wait for the server to be stopped, either by Terraform or by a termination signal
+///
+/// Termination signals cancel the token of the server to follow the same graceful path as a stop requested by Terraform.
+/// In-flight operations are given the grace period of the provider to complete before the server shuts down.
+async fn shutdown(server: &Server) {
+    let token = &server.cancellation_token;
+    let cancelled = pin!(token.cancelled());
+    let terminated = pin!(signal::terminated());
+    if let Either::Right(_) = select(cancelled, terminated).await {
+        token.cancel();
+    }
+    server.drain().await;
+}
+
+/// Initialize logging from the environment
+///
+/// Logging is enabled if either `PLUGIN_LOG_FILE` or `TF_PROVIDER_LOG` is set.
+/// Logs are written to the file `PLUGIN_LOG_FILE` if set, or to stderr otherwise,
+/// never to stdout that carries the handshake.
+/// `TF_PROVIDER_LOG` selects the format of the logs: either `json`, `pretty`, or plain text,
+/// and enables the logging of the entry and exit of every RPC.
+fn init_logging() -> Result<()> {
+    let log_file = env::var("PLUGIN_LOG_FILE").ok();
+    let log_format = env::var("TF_PROVIDER_LOG").ok();
+    if log_file.is_none() && log_format.is_none() {
+        return Ok(());
+    }
+
+    let writer = match log_file {
+        Some(path) => BoxMakeWriter::new(Mutex::new(File::create(path)?)),
+        None => BoxMakeWriter::new(std::io::stderr),
+    };
+    let span_events = match log_format {
+        Some(_) => FmtSpan::NEW | FmtSpan::CLOSE,
+        None => FmtSpan::NONE,
+    };
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::TRACE)
+        .with_ansi(false)
+        .with_span_events(span_events)
+        .with_writer(writer);
+
+    // A subscriber might already have been installed by the application embedding the provider
+    _ = match log_format.as_deref() {
+        Some("json") => builder.json().try_init(),
+        Some("pretty") => builder.pretty().try_init(),
+        _ => builder.try_init(),
+    };
+    Ok(())
+}
+
 #[derive(Copy, Clone)]
 struct SockAddrIter {
     ip: IpAddr,
@@ -286,10 +671,10 @@ impl std::net::ToSocketAddrs for SockAddrIter {
     }
 }
 
-fn listen(addrs: SockAddrIter) -> Result<(TcpListenerStream, String)> {
+fn listen(addrs: SockAddrIter) -> Result<(TcpListenerStream, SocketAddr)> {
     let listener = std::net::TcpListener::bind(addrs)?;
     listener.set_nonblocking(true)?;
-    let endpoint = listener.local_addr()?.to_string();
+    let endpoint = listener.local_addr()?;
     let listener = tokio::net::TcpListener::from_std(listener)?;
     Ok((TcpListenerStream::new(listener), endpoint))
 }
@@ -359,3 +744,122 @@ impl TlsConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::SocketAddr;
+    use std::sync::Mutex;
+
+    use tokio::sync::oneshot;
+
+    use crate::schema::{Block, Schema};
+    use crate::tfplugin6_client::ProviderClient;
+    use crate::{serve_with_ready, Diagnostics, DynamicProvider};
+
+    /// Serializes the tests that serve the provider, as they configure it through the environment
+    static ENV: Mutex<()> = Mutex::new(());
+
+    struct Empty;
+
+    impl DynamicProvider for Empty {
+        fn schema(&self, _: &mut Diagnostics) -> Option<Schema> {
+            Some(Schema {
+                version: 1,
+                block: Block::default(),
+            })
+        }
+    }
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+    }
+
+    /// Serve the `Empty` provider, and connect to it once it is ready
+    async fn serve_empty() -> ProviderClient {
+        let (ready_tx, ready_rx) = oneshot::channel();
+        tokio::spawn(serve_with_ready("empty", Empty, ready_tx));
+        ProviderClient::connect(ready_rx.await.unwrap()).await
+    }
+
+    #[test]
+    fn build_service_serves_provider() {
+        runtime().block_on(async {
+            let mut client = ProviderClient::spawn("empty", Box::new(Empty)).await;
+            let response = client.get_metadata().await;
+            assert!(response.server_capabilities.unwrap().plan_destroy);
+            assert!(response.diagnostics.is_empty());
+        });
+    }
+
+    #[test]
+    fn serve_logs_rpcs() {
+        let _env = ENV.lock().unwrap_or_else(|err| err.into_inner());
+        let log_file = std::env::temp_dir().join(format!("tf-provider-{}.log", std::process::id()));
+        std::env::set_var("PLUGIN_LOG_FILE", &log_file);
+        std::env::set_var("TF_PROVIDER_LOG", "json");
+
+        runtime().block_on(async {
+            serve_empty().await.get_provider_schema().await;
+        });
+        std::env::remove_var("PLUGIN_LOG_FILE");
+        std::env::remove_var("TF_PROVIDER_LOG");
+
+        let logs = std::fs::read_to_string(&log_file).unwrap();
+        std::fs::remove_file(&log_file).unwrap();
+        let exit = logs
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .find(|log| {
+                log["span"]["name"] == "get_provider_schema" && log["fields"]["message"] == "close"
+            })
+            .unwrap();
+        assert!(exit["fields"]["time.busy"].is_string());
+    }
+
+    /// Metrics as returned by the HTTP endpoint, if it is served
+    async fn scrape(addr: SocketAddr) -> Option<String> {
+        tokio::task::spawn_blocking(move || {
+            let mut stream = std::net::TcpStream::connect(addr).ok()?;
+            stream.write_all(b"GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            Some(response)
+        })
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn serve_metrics() {
+        let _env = ENV.lock().unwrap_or_else(|err| err.into_inner());
+        let metrics_addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        std::env::set_var("TF_PROVIDER_METRICS_ADDR", metrics_addr.to_string());
+
+        runtime().block_on(async {
+            let mut client = serve_empty().await;
+            let metrics = scrape(metrics_addr).await;
+            assert!(!metrics.is_some_and(|metrics| metrics.contains("GetMetadata")));
+
+            for _ in 0..2 {
+                client.get_metadata().await;
+            }
+
+            if cfg!(feature = "metrics") {
+                let metrics = scrape(metrics_addr).await.unwrap();
+                assert!(metrics.starts_with("HTTP/1.1 200 OK"));
+                assert!(metrics.contains(r#"tf_provider_rpc_requests_total{rpc="GetMetadata"} 2"#));
+                assert!(metrics.contains("tf_provider_rpc_in_flight 0"));
+            } else {
+                assert!(scrape(metrics_addr).await.is_none());
+            }
+        });
+        std::env::remove_var("TF_PROVIDER_METRICS_ADDR");
+    }
+}
@@ -0,0 +1,450 @@
+// This file is part of the tf-provider project
+//
+// Copyright (C) ANEO, 2024-2024. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Utilities to test providers without Terraform
+
+use std::collections::HashMap;
+
+use crate::attribute_path::AttributePath;
+use crate::diagnostics::Diagnostics;
+use crate::plan_modifier::apply_plan_modifiers;
+use crate::raw::RawValue;
+use crate::resource::DynamicResource;
+use crate::schema::{Attribute, AttributeType, Block, NestedBlock};
+use crate::value::ValueAny;
+
+/// Plan a resource change and check the consistency of the plan
+///
+/// The planner is selected the same way Terraform would select it:
+/// - `plan_create` if `prior_state` is null,
+/// - `plan_destroy` if `proposed_state` is null,
+/// - `plan_update` otherwise.
+///
+/// Like the provider server, the defaults declared in the schema are applied before `plan_create`,
+/// and the plan modifiers of the resource are applied after `plan_update`.
+///
+/// The following invariants are checked on the plan:
+/// - the planner must not fail without reporting an error,
+/// - every known value in `config_state` must be kept as-is in the planned state,
+///   regardless of the order of the elements of sets declared in the schema,
+/// - every unknown value in `config_state` must be unknown in the planned state,
+/// - replace triggers must point to existing attributes,
+/// - planning again from the planned state must yield the same plan.
+///
+/// # Arguments
+///
+/// * `resource` - Resource to plan
+/// * `prior_state` - State as stored in the Terraform state
+/// * `proposed_state` - State proposed by Terraform
+/// * `config_state` - State as declared in the Terraform file
+///
+/// # Remarks
+///
+/// Returns the diagnostics emitted by the planners, followed by the invariant violations.
+///
+/// # Examples
+///
+/// ```
+/// # use async_trait::async_trait;
+/// # use serde::{Deserialize, Serialize};
+/// # use serde_json::json;
+/// # use tf_provider::schema::{Attribute, AttributeConstraint, AttributeType, Block, Schema};
+/// # use tf_provider::testing::plan_roundtrip_check;
+/// # use tf_provider::value::{Value, ValueEmpty, ValueList, ValueString};
+/// # use tf_provider::{map, AttributePath, Diagnostics, RawValue, Resource};
+/// #[derive(Debug, Clone, Serialize, Deserialize)]
+/// struct State {
+///     id: ValueString<'static>,
+///     name: ValueString<'static>,
+///     tags: ValueList<ValueString<'static>>,
+/// }
+///
+/// /// Resource that plans an unknown id, either keeps or overwrites the name, and may sort the tags in reverse order
+/// #[derive(Default)]
+/// struct Sample {
+///     overwrite_name: bool,
+///     sort_tags: bool,
+/// }
+///
+/// impl Sample {
+///     fn plan(&self, mut state: State) -> State {
+///         if state.id.is_null() {
+///             state.id = Value::Unknown;
+///         }
+///         if self.overwrite_name {
+///             state.name = "overwritten".into();
+///         }
+///         if self.sort_tags {
+///             state.tags.as_mut_option().map(|tags| tags.sort_by(|a, b| b.cmp(a)));
+///         }
+///         state
+///     }
+/// }
+///
+/// #[async_trait]
+/// impl Resource for Sample {
+///     type State<'a> = State;
+///     type PrivateState<'a> = ValueEmpty;
+///     type ProviderMetaState<'a> = ValueEmpty;
+///
+///     fn schema(&self, _diags: &mut Diagnostics) -> Option<Schema> {
+///         let attribute = |attr_type, constraint| Attribute { attr_type, constraint, ..Default::default() };
+///         Some(Schema {
+///             version: 1,
+///             block: Block {
+///                 attributes: map! {
+///                     "id" => attribute(AttributeType::String, AttributeConstraint::Computed),
+///                     "name" => attribute(AttributeType::String, AttributeConstraint::Required),
+///                     "tags" => attribute(AttributeType::Set(AttributeType::String.into()), AttributeConstraint::Optional),
+///                 },
+///                 ..Default::default()
+///             },
+///         })
+///     }
+///
+///     async fn plan_create<'a>(
+///         &self,
+///         _diags: &mut Diagnostics,
+///         proposed_state: State,
+///         _config_state: State,
+///         _provider_meta_state: ValueEmpty,
+///     ) -> Option<(State, ValueEmpty)> {
+///         Some((self.plan(proposed_state), Value::Null))
+///     }
+///     async fn plan_update<'a>(
+///         &self,
+///         _diags: &mut Diagnostics,
+///         _prior_state: State,
+///         proposed_state: State,
+///         _config_state: State,
+///         prior_private_state: ValueEmpty,
+///         _provider_meta_state: ValueEmpty,
+///     ) -> Option<(State, ValueEmpty, Vec<AttributePath>)> {
+///         Some((self.plan(proposed_state), prior_private_state, vec![AttributePath::new("name")]))
+///     }
+/// #   async fn read<'a>(&self, _: &mut Diagnostics, state: State, private_state: ValueEmpty, _: ValueEmpty) -> Option<(State, ValueEmpty)> { Some((state, private_state)) }
+/// #   async fn plan_destroy<'a>(&self, _: &mut Diagnostics, _: State, private_state: ValueEmpty, _: ValueEmpty) -> Option<ValueEmpty> { Some(private_state) }
+/// #   async fn create<'a>(&self, _: &mut Diagnostics, state: State, _: State, private_state: ValueEmpty, _: ValueEmpty) -> Option<(State, ValueEmpty)> { Some((state, private_state)) }
+/// #   async fn update<'a>(&self, _: &mut Diagnostics, _: State, state: State, _: State, private_state: ValueEmpty, _: ValueEmpty) -> Option<(State, ValueEmpty)> { Some((state, private_state)) }
+/// #   async fn destroy<'a>(&self, _: &mut Diagnostics, _: State, _: ValueEmpty, _: ValueEmpty) -> Option<()> { Some(()) }
+///     // ...
+/// }
+///
+/// let raw = |json| {
+///     let state: State = serde_json::from_value(json).unwrap();
+///     RawValue::serialize(&mut Diagnostics::default(), &state).unwrap()
+/// };
+/// let config = raw(json!({"id": null, "name": "foo", "tags": ["a", "b"]}));
+///
+/// // Consistent plan
+/// let diags = futures::executor::block_on(plan_roundtrip_check(
+///     &Sample::default(),
+///     RawValue::default(),
+///     config.clone(),
+///     config.clone(),
+/// ));
+/// assert!(diags.errors.is_empty());
+///
+/// // Sets may be reordered
+/// let diags = futures::executor::block_on(plan_roundtrip_check(
+///     &Sample { sort_tags: true, ..Default::default() },
+///     RawValue::default(),
+///     config.clone(),
+///     config.clone(),
+/// ));
+/// assert!(diags.errors.is_empty());
+///
+/// // Inconsistent plan: the name set in the configuration is overwritten
+/// let diags = futures::executor::block_on(plan_roundtrip_check(
+///     &Sample { overwrite_name: true, ..Default::default() },
+///     raw(json!({"id": "1", "name": "foo", "tags": null})),
+///     config.clone(),
+///     config.clone(),
+/// ));
+/// assert_eq!(diags.errors.len(), 1);
+/// assert_eq!(diags.errors[0].attribute, AttributePath::new("name"));
+/// ```
+pub async fn plan_roundtrip_check(
+    resource: &dyn DynamicResource,
+    prior_state: RawValue,
+    proposed_state: RawValue,
+    config_state: RawValue,
+) -> Diagnostics {
+    let mut diags = Diagnostics::default();
+
+    let Some((planned_state, planned_private, triggers)) = plan(
+        resource,
+        &mut diags,
+        prior_state.clone(),
+        proposed_state,
+        config_state.clone(),
+        Vec::new(),
+    )
+    .await
+    else {
+        diags.internal_error();
+        return diags;
+    };
+
+    if !diags.errors.is_empty() {
+        return diags;
+    }
+
+    let mut checks = Diagnostics::default();
+    let (Some(config), Some(planned), Some(prior)) = (
+        config_state.deserialize::<ValueAny>(&mut checks),
+        planned_state.deserialize::<ValueAny>(&mut checks),
+        prior_state.deserialize::<ValueAny>(&mut checks),
+    ) else {
+        diags.add_diagnostics(checks);
+        return diags;
+    };
+
+    let schema = resource.schema(&mut Diagnostics::default());
+    let shape = match &schema {
+        Some(schema) => Shape::Block(&schema.block),
+        None => Shape::Unknown,
+    };
+    check_config(&mut checks, shape, &config, &planned, AttributePath::root());
+
+    for trigger in &triggers {
+        if planned.get_path(trigger).is_none() && prior.get_path(trigger).is_none() {
+            checks.error(
+                "Invalid replace trigger",
+                format!("`{}` does not exist in the resource", trigger),
+                trigger.clone(),
+            );
+        }
+    }
+
+    // Planning a second time from the planned state must be a no-op
+    if checks.errors.is_empty() && !planned_state.is_null() {
+        let mut replan_diags = Diagnostics::default();
+        match plan(
+            resource,
+            &mut replan_diags,
+            prior_state,
+            planned_state.clone(),
+            config_state,
+            planned_private.clone(),
+        )
+        .await
+        {
+            Some((replanned_state, replanned_private, _)) => {
+                let replanned = replanned_state.deserialize::<ValueAny>(&mut replan_diags);
+                if replanned.as_ref() != Some(&planned) || replanned_private != planned_private {
+                    checks.root_error(
+                        "Unstable plan",
+                        "Planning from the planned state yields a different plan",
+                    );
+                }
+            }
+            None => checks.root_error_short("Planning from the planned state failed"),
+        }
+        checks.add_diagnostics(replan_diags);
+    }
+
+    diags.add_diagnostics(checks);
+    diags
+}
+
+async fn plan(
+    resource: &dyn DynamicResource,
+    diags: &mut Diagnostics,
+    prior_state: RawValue,
+    proposed_state: RawValue,
+    config_state: RawValue,
+    prior_private: Vec<u8>,
+) -> Option<(RawValue, Vec<u8>, Vec<AttributePath>)> {
+    match (prior_state.is_null(), proposed_state.is_null()) {
+        (true, false) => {
+            let proposed_state = match resource.schema(&mut Diagnostics::default()) {
+                Some(schema) => schema.apply_defaults(diags, proposed_state)?,
+                None => proposed_state,
+            };
+            let (state, private_state) = resource
+                .plan_create(diags, proposed_state, config_state, RawValue::default())
+                .await?;
+            Some((state, private_state, vec![]))
+        }
+        (false, false) => {
+            let (state, private_state, triggers) = resource
+                .plan_update(
+                    diags,
+                    prior_state.clone(),
+                    proposed_state,
+                    config_state.clone(),
+                    prior_private,
+                    RawValue::default(),
+                )
+                .await?;
+            let (state, triggers) = apply_plan_modifiers(
+                diags,
+                &resource.plan_modifiers(),
+                &prior_state,
+                &config_state,
+                state,
+                triggers,
+            )?;
+            Some((state, private_state, triggers))
+        }
+        (false, true) => {
+            let private_state = resource
+                .plan_destroy(diags, prior_state, prior_private, RawValue::default())
+                .await?;
+            Some((RawValue::default(), private_state, vec![]))
+        }
+        (true, true) => {
+            diags.root_error_short("Resource is planned both for creation and deletion");
+            None
+        }
+    }
+}
+
+/// Part of the schema describing a value, used to know which lists are sets
+#[derive(Clone, Copy)]
+enum Shape<'a> {
+    Unknown,
+    Block(&'a Block),
+    Attributes(&'a HashMap<String, Attribute>),
+    Type(&'a AttributeType),
+    Nested(&'a NestedBlock),
+}
+
+impl<'a> Shape<'a> {
+    fn attribute(self, name: &str) -> Self {
+        match self {
+            Shape::Block(block) => match (block.attributes.get(name), block.blocks.get(name)) {
+                (Some(attribute), _) => Shape::Type(&attribute.attr_type),
+                (None, Some(nested)) => Shape::Nested(nested),
+                (None, None) => Shape::Unknown,
+            },
+            Shape::Attributes(attributes) => {
+                attributes.get(name).map_or(Shape::Unknown, |attribute| {
+                    Shape::Type(&attribute.attr_type)
+                })
+            }
+            Shape::Type(AttributeType::Object(fields)) => {
+                fields.get(name).map_or(Shape::Unknown, Shape::Type)
+            }
+            Shape::Type(AttributeType::AttributeSingle(attributes)) => {
+                Shape::Attributes(attributes).attribute(name)
+            }
+            Shape::Type(AttributeType::Map(inner)) => Shape::Type(inner),
+            Shape::Type(AttributeType::AttributeMap(attributes)) => Shape::Attributes(attributes),
+            Shape::Nested(
+                NestedBlock::Single(block)
+                | NestedBlock::Group(block)
+                | NestedBlock::Optional(block),
+            ) => Shape::Block(block).attribute(name),
+            Shape::Nested(NestedBlock::Map(block)) => Shape::Block(block),
+            _ => Shape::Unknown,
+        }
+    }
+
+    fn element(self, index: usize) -> Self {
+        match self {
+            Shape::Type(AttributeType::List(inner) | AttributeType::Set(inner)) => {
+                Shape::Type(inner)
+            }
+            Shape::Type(AttributeType::Tuple(types)) => {
+                types.get(index).map_or(Shape::Unknown, Shape::Type)
+            }
+            Shape::Type(
+                AttributeType::AttributeList(attributes) | AttributeType::AttributeSet(attributes),
+            ) => Shape::Attributes(attributes),
+            Shape::Nested(NestedBlock::List(block) | NestedBlock::Set(block)) => {
+                Shape::Block(block)
+            }
+            _ => Shape::Unknown,
+        }
+    }
+
+    fn is_set(self) -> bool {
+        matches!(
+            self,
+            Shape::Type(AttributeType::Set(_) | AttributeType::AttributeSet(_))
+                | Shape::Nested(NestedBlock::Set(_))
+        )
+    }
+}
+
+fn check_config(
+    diags: &mut Diagnostics,
+    shape: Shape,
+    config: &ValueAny,
+    planned: &ValueAny,
+    path: AttributePath,
+) {
+    match (config, planned) {
+        (ValueAny::Null, _) => (),
+        (ValueAny::Unknown, ValueAny::Unknown) => (),
+        (ValueAny::Unknown, _) => diags.error(
+            "Provider produced invalid plan",
+            "The configured value is unknown, but the planned value is known",
+            path,
+        ),
+        (ValueAny::Map(config), ValueAny::Map(planned)) => {
+            for (key, config) in config {
+                let planned = planned.get(key).unwrap_or(&ValueAny::Null);
+                let path = path.clone().attribute(key.clone());
+                check_config(diags, shape.attribute(key), config, planned, path);
+            }
+        }
+        // Terraform does not preserve the order of set elements:
+        // every configured element must match its own planned element
+        (ValueAny::List(config), ValueAny::List(planned))
+            if shape.is_set() && config.len() == planned.len() =>
+        {
+            let shape = shape.element(0);
+            let mut matched = vec![false; planned.len()];
+            for (i, config) in config.iter().enumerate() {
+                let found = (0..planned.len()).filter(|&j| !matched[j]).find(|&j| {
+                    let mut scratch = Diagnostics::default();
+                    check_config(&mut scratch, shape, config, &planned[j], path.clone());
+                    scratch.errors.is_empty()
+                });
+                match found {
+                    Some(j) => matched[j] = true,
+                    None => diags.error(
+                        "Provider produced invalid plan",
+                        format!(
+                            "The configured set element {} has no match in the planned value",
+                            config.json()
+                        ),
+                        path.clone().index(i as i64),
+                    ),
+                }
+            }
+        }
+        (ValueAny::List(config), ValueAny::List(planned)) if config.len() == planned.len() => {
+            for (i, (config, planned)) in config.iter().zip(planned).enumerate() {
+                let path = path.clone().index(i as i64);
+                check_config(diags, shape.element(i), config, planned, path);
+            }
+        }
+        (config, planned) if config == planned => (),
+        (config, planned) => diags.error(
+            "Provider produced invalid plan",
+            format!(
+                "The planned value {} does not match the configured value {}",
+                planned.json(),
+                config.json()
+            ),
+            path,
+        ),
+    }
+}
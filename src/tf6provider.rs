@@ -21,6 +21,7 @@ use std::sync::Arc;
 
 use crate::attribute_path::AttributePathStep;
 use crate::diagnostics::Diagnostics;
+use crate::plan_modifier::apply_plan_modifiers;
 use crate::raw::RawValue;
 use crate::server::Server;
 use crate::tfplugin6 as tf;
@@ -28,10 +29,12 @@ use crate::utils::{CollectDiagnostics, OptionExpand};
 
 #[tonic::async_trait]
 impl tf::provider_server::Provider for Arc<Server> {
+    #[tracing::instrument(skip_all)]
     async fn get_metadata(
         &self,
         _request: tonic::Request<tf::get_metadata::Request>,
     ) -> std::result::Result<tonic::Response<tf::get_metadata::Response>, tonic::Status> {
+        let _timer = self.metrics.start("GetMetadata");
         let resources = self
             .resources
             .keys()
@@ -55,7 +58,7 @@ impl tf::provider_server::Provider for Arc<Server> {
             server_capabilities: Some(tf::ServerCapabilities {
                 plan_destroy: true,
                 get_provider_schema_optional: false,
-                move_resource_state: false,
+                move_resource_state: self.can_move_state(),
             }),
             diagnostics: self.init_diags.clone().into(),
             data_sources,
@@ -63,10 +66,12 @@ impl tf::provider_server::Provider for Arc<Server> {
             functions,
         }))
     }
+    #[tracing::instrument(skip_all)]
     async fn get_provider_schema(
         &self,
         _request: tonic::Request<tf::get_provider_schema::Request>,
     ) -> Result<tonic::Response<tf::get_provider_schema::Response>, tonic::Status> {
+        let _timer = self.metrics.start("GetProviderSchema");
         let schema = self.schema.as_ref().map(Into::into);
         let meta_schema = self.meta_schema.as_ref().map(Into::into);
         let resources = self
@@ -94,20 +99,24 @@ impl tf::provider_server::Provider for Arc<Server> {
             server_capabilities: Some(tf::ServerCapabilities {
                 plan_destroy: true,
                 get_provider_schema_optional: false,
-                move_resource_state: false,
+                move_resource_state: self.can_move_state(),
             }),
             functions,
         }))
     }
+    #[tracing::instrument(skip_all)]
     async fn validate_provider_config(
         &self,
         request: tonic::Request<tf::validate_provider_config::Request>,
     ) -> Result<tonic::Response<tf::validate_provider_config::Response>, tonic::Status> {
+        let _timer = self.metrics.start("ValidateProviderConfig");
         let request = request.into_inner();
         let mut diags = Diagnostics::default();
 
         _ = if let Some(config) = request.config {
-            self.provider.validate(&mut diags, config.into()).await
+            self.track(self.provider.validate(&mut diags, config.into()))
+                .await
+                .unwrap_or_else(|| self.aborted(&mut diags))
         } else {
             None
         }
@@ -119,10 +128,12 @@ impl tf::provider_server::Provider for Arc<Server> {
             },
         ))
     }
+    #[tracing::instrument(skip_all, fields(type_name = %request.get_ref().type_name))]
     async fn validate_resource_config(
         &self,
         request: tonic::Request<tf::validate_resource_config::Request>,
     ) -> Result<tonic::Response<tf::validate_resource_config::Response>, tonic::Status> {
+        let _timer = self.metrics.start("ValidateResourceConfig");
         let request = request.into_inner();
         let mut diags = Diagnostics::default();
 
@@ -130,7 +141,9 @@ impl tf::provider_server::Provider for Arc<Server> {
             self.get_resource(&mut diags, &request.type_name),
             request.config,
         ) {
-            resource.validate(&mut diags, config.into()).await
+            self.track(resource.validate(&mut diags, config.into()))
+                .await
+                .unwrap_or_else(|| self.aborted(&mut diags))
         } else {
             None
         }
@@ -142,10 +155,12 @@ impl tf::provider_server::Provider for Arc<Server> {
             },
         ))
     }
+    #[tracing::instrument(skip_all, fields(type_name = %request.get_ref().type_name))]
     async fn validate_data_resource_config(
         &self,
         request: tonic::Request<tf::validate_data_resource_config::Request>,
     ) -> Result<tonic::Response<tf::validate_data_resource_config::Response>, tonic::Status> {
+        let _timer = self.metrics.start("ValidateDataResourceConfig");
         let request = request.into_inner();
         let mut diags = Diagnostics::default();
 
@@ -153,7 +168,9 @@ impl tf::provider_server::Provider for Arc<Server> {
             self.get_data_source(&mut diags, &request.type_name),
             request.config,
         ) {
-            data_source.validate(&mut diags, config.into()).await
+            self.track(data_source.validate(&mut diags, config.into()))
+                .await
+                .unwrap_or_else(|| self.aborted(&mut diags))
         } else {
             None
         }
@@ -165,10 +182,12 @@ impl tf::provider_server::Provider for Arc<Server> {
             },
         ))
     }
+    #[tracing::instrument(skip_all, fields(type_name = %request.get_ref().type_name))]
     async fn upgrade_resource_state(
         &self,
         request: tonic::Request<tf::upgrade_resource_state::Request>,
     ) -> Result<tonic::Response<tf::upgrade_resource_state::Response>, tonic::Status> {
+        let _timer = self.metrics.start("UpgradeResourceState");
         let request = request.into_inner();
         let mut diags = Diagnostics::default();
 
@@ -183,7 +202,9 @@ impl tf::provider_server::Provider for Arc<Server> {
                 if request.version == schema.version {
                     Some(json)
                 } else {
-                    resource.upgrade(&mut diags, request.version, json).await
+                    self.track(resource.upgrade(&mut diags, request.version, json))
+                        .await
+                        .unwrap_or_else(|| self.aborted(&mut diags))
                 }
             }
         } else {
@@ -197,18 +218,24 @@ impl tf::provider_server::Provider for Arc<Server> {
         }))
     }
     /// ////// One-time initialization, called before other functions below
+    #[tracing::instrument(skip_all)]
     async fn configure_provider(
         &self,
         request: tonic::Request<tf::configure_provider::Request>,
     ) -> Result<tonic::Response<tf::configure_provider::Response>, tonic::Status> {
+        let _timer = self.metrics.start("ConfigureProvider");
         let request = request.into_inner();
         let mut diags = Diagnostics::default();
 
         if let Some(config) = request.config {
-            if self
-                .provider
-                .configure(&mut diags, request.terraform_version, config.into())
+            let configured = self.track(self.provider.configure(
+                &mut diags,
+                request.terraform_version,
+                config.into(),
+            ));
+            if configured
                 .await
+                .unwrap_or_else(|| self.aborted(&mut diags))
                 .is_none()
             {
                 diags.internal_error();
@@ -220,10 +247,12 @@ impl tf::provider_server::Provider for Arc<Server> {
         }))
     }
     /// ////// Managed Resource Lifecycle
+    #[tracing::instrument(skip_all, fields(type_name = %request.get_ref().type_name))]
     async fn read_resource(
         &self,
         request: tonic::Request<tf::read_resource::Request>,
     ) -> Result<tonic::Response<tf::read_resource::Response>, tonic::Status> {
+        let _timer = self.metrics.start("ReadResource");
         let request = request.into_inner();
         let mut diags = Diagnostics::default();
 
@@ -233,14 +262,14 @@ impl tf::provider_server::Provider for Arc<Server> {
                 request.current_state,
                 request.provider_meta,
             ) {
-                resource
-                    .read(
-                        &mut diags,
-                        state.into(),
-                        request.private,
-                        provider_meta_state.into(),
-                    )
-                    .await
+                self.track(resource.read(
+                    &mut diags,
+                    state.into(),
+                    request.private,
+                    provider_meta_state.into(),
+                ))
+                .await
+                .unwrap_or_else(|| self.aborted(&mut diags))
             } else {
                 None
             }
@@ -252,10 +281,12 @@ impl tf::provider_server::Provider for Arc<Server> {
             private: private_state.unwrap_or_default(),
         }))
     }
+    #[tracing::instrument(skip_all, fields(type_name = %request.get_ref().type_name))]
     async fn plan_resource_change(
         &self,
         request: tonic::Request<tf::plan_resource_change::Request>,
     ) -> Result<tonic::Response<tf::plan_resource_change::Response>, tonic::Status> {
+        let _timer = self.metrics.start("PlanResourceChange");
         let request = request.into_inner();
         let mut diags = Diagnostics::default();
 
@@ -282,43 +313,61 @@ impl tf::provider_server::Provider for Arc<Server> {
                 config_state.is_null(),
             ) {
                 (false, false, false) => {
-                    resource
-                        .plan_update(
+                    let planned = self.track(resource.plan_update(
+                        &mut diags,
+                        prior_state.clone(),
+                        proposed_state,
+                        config_state.clone(),
+                        request.prior_private,
+                        provider_meta_state.into(),
+                    ));
+                    if let Some((state, private_state, triggers)) =
+                        planned.await.unwrap_or_else(|| self.aborted(&mut diags))
+                    {
+                        apply_plan_modifiers(
                             &mut diags,
-                            prior_state,
-                            proposed_state,
-                            config_state,
-                            request.prior_private,
-                            provider_meta_state.into(),
+                            &resource.plan_modifiers(),
+                            &prior_state,
+                            &config_state,
+                            state,
+                            triggers,
                         )
-                        .await
+                        .map(|(state, triggers)| (state, private_state, triggers))
+                    } else {
+                        None
+                    }
                 }
-                (false, true, true) => {
-                    let planned_private = resource
-                        .plan_destroy(
-                            &mut diags,
-                            prior_state,
-                            request.prior_private,
-                            provider_meta_state.into(),
-                        )
-                        .await;
-                    Some((
-                        Default::default(),
-                        planned_private.unwrap_or_default(),
-                        vec![],
+                (false, true, true) => self
+                    .track(resource.plan_destroy(
+                        &mut diags,
+                        prior_state,
+                        request.prior_private,
+                        provider_meta_state.into(),
                     ))
-                }
+                    .await
+                    .map(|planned_private| {
+                        (
+                            Default::default(),
+                            planned_private.unwrap_or_default(),
+                            vec![],
+                        )
+                    })
+                    .or_else(|| self.aborted(&mut diags)),
                 (true, false, false) => {
-                    if let Some((state, private_state)) = resource
-                        .plan_create(
+                    let proposed_state = match self.resources.get(&request.type_name) {
+                        Some((_, schema)) => schema.apply_defaults(&mut diags, proposed_state),
+                        None => Some(proposed_state),
+                    };
+                    if let Some(proposed_state) = proposed_state {
+                        self.track(resource.plan_create(
                             &mut diags,
                             proposed_state,
                             config_state,
                             provider_meta_state.into(),
-                        )
+                        ))
                         .await
-                    {
-                        Some((state, private_state, vec![]))
+                        .unwrap_or_else(|| self.aborted(&mut diags))
+                        .map(|(state, private_state)| (state, private_state, vec![]))
                     } else {
                         None
                     }
@@ -349,10 +398,12 @@ impl tf::provider_server::Provider for Arc<Server> {
             legacy_type_system: false,
         }))
     }
+    #[tracing::instrument(skip_all, fields(type_name = %request.get_ref().type_name))]
     async fn apply_resource_change(
         &self,
         request: tonic::Request<tf::apply_resource_change::Request>,
     ) -> Result<tonic::Response<tf::apply_resource_change::Response>, tonic::Status> {
+        let _timer = self.metrics.start("ApplyResourceChange");
         let request = request.into_inner();
         let mut diags = Diagnostics::default();
 
@@ -378,40 +429,37 @@ impl tf::provider_server::Provider for Arc<Server> {
                 planned_state.is_null(),
                 config_state.is_null(),
             ) {
-                (false, false, false) => {
-                    resource
-                        .update(
-                            &mut diags,
-                            prior_state,
-                            planned_state,
-                            config_state,
-                            request.planned_private,
-                            provider_meta_state.into(),
-                        )
-                        .await
-                }
-                (false, true, true) => {
-                    resource
-                        .destroy(
-                            &mut diags,
-                            prior_state,
-                            request.planned_private,
-                            provider_meta_state.into(),
-                        )
-                        .await;
-                    Some((Default::default(), vec![]))
-                }
-                (true, false, false) => {
-                    resource
-                        .create(
-                            &mut diags,
-                            planned_state,
-                            config_state,
-                            request.planned_private,
-                            provider_meta_state.into(),
-                        )
-                        .await
-                }
+                (false, false, false) => self
+                    .track(resource.update(
+                        &mut diags,
+                        prior_state,
+                        planned_state,
+                        config_state,
+                        request.planned_private,
+                        provider_meta_state.into(),
+                    ))
+                    .await
+                    .unwrap_or_else(|| self.aborted(&mut diags)),
+                (false, true, true) => self
+                    .track(resource.destroy(
+                        &mut diags,
+                        prior_state,
+                        request.planned_private,
+                        provider_meta_state.into(),
+                    ))
+                    .await
+                    .map(|_| (Default::default(), vec![]))
+                    .or_else(|| self.aborted(&mut diags)),
+                (true, false, false) => self
+                    .track(resource.create(
+                        &mut diags,
+                        planned_state,
+                        config_state,
+                        request.planned_private,
+                        provider_meta_state.into(),
+                    ))
+                    .await
+                    .unwrap_or_else(|| self.aborted(&mut diags)),
                 _ => {
                     diags.root_error_short("Resource is marked both for creation and deletion");
                     None
@@ -430,15 +478,19 @@ impl tf::provider_server::Provider for Arc<Server> {
             legacy_type_system: false,
         }))
     }
+    #[tracing::instrument(skip_all, fields(type_name = %request.get_ref().type_name))]
     async fn import_resource_state(
         &self,
         request: tonic::Request<tf::import_resource_state::Request>,
     ) -> Result<tonic::Response<tf::import_resource_state::Response>, tonic::Status> {
+        let _timer = self.metrics.start("ImportResourceState");
         let request = request.into_inner();
         let mut diags = Diagnostics::default();
 
         let imported = if let Some(resource) = self.get_resource(&mut diags, &request.type_name) {
-            resource.import(&mut diags, request.id).await
+            self.track(resource.import(&mut diags, request.id))
+                .await
+                .unwrap_or_else(|| self.aborted(&mut diags))
         } else {
             None
         }
@@ -457,23 +509,59 @@ impl tf::provider_server::Provider for Arc<Server> {
         }))
     }
 
+    #[tracing::instrument(skip_all, fields(target_type_name = %request.get_ref().target_type_name))]
     async fn move_resource_state(
         &self,
-        _request: tonic::Request<tf::move_resource_state::Request>,
+        request: tonic::Request<tf::move_resource_state::Request>,
     ) -> std::result::Result<tonic::Response<tf::move_resource_state::Response>, tonic::Status>
     {
+        let _timer = self.metrics.start("MoveResourceState");
+        let request = request.into_inner();
         let mut diags = Diagnostics::default();
-        diags.root_error_short("MoveResourceState is not implemented");
+
+        let (state, private_state) = if let (Some(resource), Some(source_state)) = (
+            self.get_resource(&mut diags, &request.target_type_name),
+            request.source_state,
+        ) {
+            if source_state.json.is_empty() {
+                diags.root_error_short("Moving from a legacy state is not supported");
+                None
+            } else if !resource.can_move_state() {
+                diags.root_error_short(format!(
+                    "Resource `{}` cannot be moved from another resource type",
+                    request.target_type_name
+                ));
+                None
+            } else {
+                self.track(resource.move_state(
+                    &mut diags,
+                    request.source_provider_address,
+                    request.source_type_name,
+                    request.source_schema_version,
+                    RawValue::Json(source_state.json),
+                    request.source_private,
+                ))
+                .await
+                .unwrap_or_else(|| self.aborted(&mut diags))
+            }
+        } else {
+            None
+        }
+        .collect_diagnostics(&mut diags)
+        .expand();
+
         Ok(tonic::Response::new(tf::move_resource_state::Response {
             diagnostics: diags.into(),
-            target_private: vec![],
-            target_state: None,
+            target_private: private_state.unwrap_or_default(),
+            target_state: state.map(Into::into),
         }))
     }
+    #[tracing::instrument(skip_all, fields(type_name = %request.get_ref().type_name))]
     async fn read_data_source(
         &self,
         request: tonic::Request<tf::read_data_source::Request>,
     ) -> Result<tonic::Response<tf::read_data_source::Response>, tonic::Status> {
+        let _timer = self.metrics.start("ReadDataSource");
         let request = request.into_inner();
         let mut diags = Diagnostics::default();
 
@@ -482,9 +570,9 @@ impl tf::provider_server::Provider for Arc<Server> {
             request.config,
             request.provider_meta,
         ) {
-            data_source
-                .read(&mut diags, config.into(), provider_meta_state.into())
+            self.track(data_source.read(&mut diags, config.into(), provider_meta_state.into()))
                 .await
+                .unwrap_or_else(|| self.aborted(&mut diags))
         } else {
             None
         }
@@ -495,10 +583,12 @@ impl tf::provider_server::Provider for Arc<Server> {
             diagnostics: diags.into(),
         }))
     }
+    #[tracing::instrument(skip_all)]
     async fn get_functions(
         &self,
         _request: tonic::Request<tf::get_functions::Request>,
     ) -> std::result::Result<tonic::Response<tf::get_functions::Response>, tonic::Status> {
+        let _timer = self.metrics.start("GetFunctions");
         let functions = self
             .functions
             .iter()
@@ -509,20 +599,22 @@ impl tf::provider_server::Provider for Arc<Server> {
             functions,
         }))
     }
+    #[tracing::instrument(skip_all, fields(name = %request.get_ref().name))]
     async fn call_function(
         &self,
         request: tonic::Request<tf::call_function::Request>,
     ) -> std::result::Result<tonic::Response<tf::call_function::Response>, tonic::Status> {
+        let _timer = self.metrics.start("CallFunction");
         let request = request.into_inner();
         let mut diags = Diagnostics::default();
 
         let result = if let Some(function) = self.get_function(&mut diags, &request.name) {
-            function
-                .call(
-                    &mut diags,
-                    request.arguments.into_iter().map(Into::into).collect(),
-                )
-                .await
+            self.track(function.call(
+                &mut diags,
+                request.arguments.into_iter().map(Into::into).collect(),
+            ))
+            .await
+            .unwrap_or_else(|| self.aborted(&mut diags))
         } else {
             None
         }
@@ -603,10 +695,13 @@ impl tf::provider_server::Provider for Arc<Server> {
         }))
     }
     /// ////// Graceful Shutdown
+    #[tracing::instrument(skip_all)]
     async fn stop_provider(
         &self,
         _request: tonic::Request<tf::stop_provider::Request>,
     ) -> Result<tonic::Response<tf::stop_provider::Response>, tonic::Status> {
+        let _timer = self.metrics.start("StopProvider");
+        // In-flight operations are aborted once the grace period has elapsed
         self.cancellation_token.cancel();
 
         Ok(tonic::Response::new(tf::stop_provider::Response {
@@ -0,0 +1,111 @@
+// This file is part of the tf-provider project
+//
+// Copyright (C) ANEO, 2024-2024. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Minimal `tfplugin6` client to test the provider over gRPC, as Terraform would call it
+//!
+//! The generated client is not built, so only the RPCs used by the tests are provided here.
+//! Calls panic on transport errors and on error statuses.
+//!
+//! The module is only compiled with the tests.
+
+use std::net::SocketAddr;
+
+use tonic::client::Grpc;
+use tonic::codec::ProstCodec;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::transport::Channel;
+
+use crate::provider::DynamicProvider;
+use crate::tfplugin6::{call_function, get_metadata, get_provider_schema, stop_provider};
+
+#[derive(Clone)]
+pub(crate) struct ProviderClient {
+    inner: Grpc<Channel>,
+}
+
+impl ProviderClient {
+    /// Connect to a provider served on `addr` without TLS
+    pub(crate) async fn connect(addr: SocketAddr) -> Self {
+        let channel = Channel::from_shared(format!("http://{addr}"))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        Self {
+            inner: Grpc::new(channel),
+        }
+    }
+
+    /// Serve the provider with [`crate::build_service_dynamic`] on a local port, and connect to it
+    ///
+    /// Must be called within a tokio runtime, the server runs until the runtime is dropped.
+    pub(crate) async fn spawn(name: &str, provider: Box<dyn DynamicProvider>) -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(crate::build_service_dynamic(name.to_owned(), provider))
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+        );
+        Self::connect(addr).await
+    }
+
+    pub(crate) async fn get_metadata(&mut self) -> get_metadata::Response {
+        self.unary("/tfplugin6.Provider/GetMetadata", get_metadata::Request {})
+            .await
+    }
+
+    pub(crate) async fn get_provider_schema(&mut self) -> get_provider_schema::Response {
+        self.unary(
+            "/tfplugin6.Provider/GetProviderSchema",
+            get_provider_schema::Request {},
+        )
+        .await
+    }
+
+    pub(crate) async fn call_function(
+        &mut self,
+        request: call_function::Request,
+    ) -> call_function::Response {
+        self.unary("/tfplugin6.Provider/CallFunction", request)
+            .await
+    }
+
+    pub(crate) async fn stop_provider(&mut self) -> stop_provider::Response {
+        self.unary(
+            "/tfplugin6.Provider/StopProvider",
+            stop_provider::Request {},
+        )
+        .await
+    }
+
+    async fn unary<Request, Response>(&mut self, path: &'static str, request: Request) -> Response
+    where
+        Request: prost::Message + Send + Sync + 'static,
+        Response: prost::Message + Default + Send + Sync + 'static,
+    {
+        self.inner.ready().await.unwrap();
+        self.inner
+            .unary(
+                tonic::Request::new(request),
+                PathAndQuery::from_static(path),
+                ProstCodec::default(),
+            )
+            .await
+            .unwrap()
+            .into_inner()
+    }
+}
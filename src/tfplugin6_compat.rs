@@ -0,0 +1,366 @@
+// This file is part of the tf-provider project
+//
+// Copyright (C) ANEO, 2024-2024. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compile-time checks of the `tfplugin6` messages used by the provider
+//!
+//! Every message the provider emits is built here with all its fields,
+//! and every request the provider receives is destructured with all its fields.
+//! No `..Default::default()` nor `..` is used on purpose:
+//! when the proto is regenerated, a renamed, removed, or added field fails the build here,
+//! pointing at the message to review in the conversions of `schema.rs`, `diagnostics.rs`, and `tf6provider.rs`.
+//!
+//! The module is only compiled with the tests, and its functions are never called.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use crate::tfplugin6 as tf;
+
+fn dynamic_value() -> tf::DynamicValue {
+    tf::DynamicValue {
+        msgpack: Vec::new(),
+        json: Vec::new(),
+    }
+}
+
+fn attribute_path() -> tf::AttributePath {
+    use tf::attribute_path::step::Selector;
+    tf::AttributePath {
+        steps: vec![
+            tf::attribute_path::Step {
+                selector: Some(Selector::AttributeName(String::new())),
+            },
+            tf::attribute_path::Step {
+                selector: Some(Selector::ElementKeyString(String::new())),
+            },
+            tf::attribute_path::Step {
+                selector: Some(Selector::ElementKeyInt(0)),
+            },
+        ],
+    }
+}
+
+fn diagnostics() -> Vec<tf::Diagnostic> {
+    use tf::diagnostic::Severity;
+    [Severity::Invalid, Severity::Error, Severity::Warning]
+        .into_iter()
+        .map(|severity| tf::Diagnostic {
+            severity: severity as i32,
+            summary: String::new(),
+            detail: String::new(),
+            attribute: Some(attribute_path()),
+        })
+        .collect()
+}
+
+fn function_error() -> tf::FunctionError {
+    tf::FunctionError {
+        text: String::new(),
+        function_argument: Some(0),
+    }
+}
+
+fn server_capabilities() -> tf::ServerCapabilities {
+    tf::ServerCapabilities {
+        plan_destroy: true,
+        get_provider_schema_optional: false,
+        move_resource_state: false,
+    }
+}
+
+#[allow(deprecated)]
+fn schema() -> tf::Schema {
+    use tf::schema::{nested_block, object};
+    let attribute = tf::schema::Attribute {
+        name: String::new(),
+        r#type: Vec::new(),
+        nested_type: Some(tf::schema::Object {
+            attributes: Vec::new(),
+            nesting: object::NestingMode::Single as i32,
+            min_items: 0,
+            max_items: 1,
+        }),
+        description: String::new(),
+        required: false,
+        optional: false,
+        computed: false,
+        sensitive: false,
+        description_kind: tf::StringKind::Plain as i32,
+        deprecated: false,
+    };
+    let block = |block_types| tf::schema::Block {
+        version: 0,
+        attributes: vec![attribute.clone()],
+        block_types,
+        description: String::new(),
+        description_kind: tf::StringKind::Markdown as i32,
+        deprecated: false,
+    };
+    let nested_block = tf::schema::NestedBlock {
+        type_name: String::new(),
+        block: Some(block(Vec::new())),
+        nesting: nested_block::NestingMode::List as i32,
+        min_items: 0,
+        max_items: 0,
+    };
+    tf::Schema {
+        version: 0,
+        block: Some(block(vec![nested_block])),
+    }
+}
+
+fn function() -> tf::Function {
+    let parameter = tf::function::Parameter {
+        name: String::new(),
+        r#type: Vec::new(),
+        allow_null_value: false,
+        allow_unknown_values: false,
+        description: String::new(),
+        description_kind: tf::StringKind::Plain as i32,
+    };
+    tf::Function {
+        parameters: vec![parameter.clone()],
+        variadic_parameter: Some(parameter),
+        r#return: Some(tf::function::Return { r#type: Vec::new() }),
+        summary: String::new(),
+        description: String::new(),
+        description_kind: tf::StringKind::Plain as i32,
+        deprecation_message: String::new(),
+    }
+}
+
+fn get_metadata(request: tf::get_metadata::Request) -> tf::get_metadata::Response {
+    let tf::get_metadata::Request {} = request;
+    tf::get_metadata::Response {
+        server_capabilities: Some(server_capabilities()),
+        diagnostics: diagnostics(),
+        data_sources: vec![tf::get_metadata::DataSourceMetadata {
+            type_name: String::new(),
+        }],
+        resources: vec![tf::get_metadata::ResourceMetadata {
+            type_name: String::new(),
+        }],
+        functions: vec![tf::get_metadata::FunctionMetadata {
+            name: String::new(),
+        }],
+    }
+}
+
+fn get_provider_schema(
+    request: tf::get_provider_schema::Request,
+) -> tf::get_provider_schema::Response {
+    let tf::get_provider_schema::Request {} = request;
+    tf::get_provider_schema::Response {
+        provider: Some(schema()),
+        resource_schemas: HashMap::from([(String::new(), schema())]),
+        data_source_schemas: HashMap::from([(String::new(), schema())]),
+        functions: HashMap::from([(String::new(), function())]),
+        diagnostics: diagnostics(),
+        provider_meta: Some(schema()),
+        server_capabilities: Some(server_capabilities()),
+    }
+}
+
+fn validate_provider_config(
+    request: tf::validate_provider_config::Request,
+) -> tf::validate_provider_config::Response {
+    let tf::validate_provider_config::Request { config: _ } = request;
+    tf::validate_provider_config::Response {
+        diagnostics: diagnostics(),
+    }
+}
+
+fn validate_resource_config(
+    request: tf::validate_resource_config::Request,
+) -> tf::validate_resource_config::Response {
+    let tf::validate_resource_config::Request {
+        type_name: _,
+        config: _,
+    } = request;
+    tf::validate_resource_config::Response {
+        diagnostics: diagnostics(),
+    }
+}
+
+fn validate_data_resource_config(
+    request: tf::validate_data_resource_config::Request,
+) -> tf::validate_data_resource_config::Response {
+    let tf::validate_data_resource_config::Request {
+        type_name: _,
+        config: _,
+    } = request;
+    tf::validate_data_resource_config::Response {
+        diagnostics: diagnostics(),
+    }
+}
+
+fn upgrade_resource_state(
+    request: tf::upgrade_resource_state::Request,
+) -> tf::upgrade_resource_state::Response {
+    let tf::upgrade_resource_state::Request {
+        type_name: _,
+        version: _,
+        raw_state,
+    } = request;
+    if let Some(tf::RawState {
+        json: _,
+        flatmap: _,
+    }) = raw_state
+    {}
+    tf::upgrade_resource_state::Response {
+        upgraded_state: Some(dynamic_value()),
+        diagnostics: diagnostics(),
+    }
+}
+
+fn configure_provider(
+    request: tf::configure_provider::Request,
+) -> tf::configure_provider::Response {
+    let tf::configure_provider::Request {
+        terraform_version: _,
+        config: _,
+    } = request;
+    tf::configure_provider::Response {
+        diagnostics: diagnostics(),
+    }
+}
+
+fn read_resource(request: tf::read_resource::Request) -> tf::read_resource::Response {
+    let tf::read_resource::Request {
+        type_name: _,
+        current_state: _,
+        private: _,
+        provider_meta: _,
+    } = request;
+    tf::read_resource::Response {
+        new_state: Some(dynamic_value()),
+        diagnostics: diagnostics(),
+        private: Vec::new(),
+    }
+}
+
+fn plan_resource_change(
+    request: tf::plan_resource_change::Request,
+) -> tf::plan_resource_change::Response {
+    let tf::plan_resource_change::Request {
+        type_name: _,
+        prior_state: _,
+        proposed_new_state: _,
+        config: _,
+        prior_private: _,
+        provider_meta: _,
+    } = request;
+    tf::plan_resource_change::Response {
+        planned_state: Some(dynamic_value()),
+        requires_replace: vec![attribute_path()],
+        planned_private: Vec::new(),
+        diagnostics: diagnostics(),
+        legacy_type_system: false,
+    }
+}
+
+fn apply_resource_change(
+    request: tf::apply_resource_change::Request,
+) -> tf::apply_resource_change::Response {
+    let tf::apply_resource_change::Request {
+        type_name: _,
+        prior_state: _,
+        planned_state: _,
+        config: _,
+        planned_private: _,
+        provider_meta: _,
+    } = request;
+    tf::apply_resource_change::Response {
+        new_state: Some(dynamic_value()),
+        private: Vec::new(),
+        diagnostics: diagnostics(),
+        legacy_type_system: false,
+    }
+}
+
+fn import_resource_state(
+    request: tf::import_resource_state::Request,
+) -> tf::import_resource_state::Response {
+    let tf::import_resource_state::Request {
+        type_name: _,
+        id: _,
+    } = request;
+    tf::import_resource_state::Response {
+        imported_resources: vec![tf::import_resource_state::ImportedResource {
+            type_name: String::new(),
+            state: Some(dynamic_value()),
+            private: Vec::new(),
+        }],
+        diagnostics: diagnostics(),
+    }
+}
+
+fn move_resource_state(
+    request: tf::move_resource_state::Request,
+) -> tf::move_resource_state::Response {
+    let tf::move_resource_state::Request {
+        source_provider_address: _,
+        source_type_name: _,
+        source_schema_version: _,
+        source_state: _,
+        target_type_name: _,
+        source_private: _,
+    } = request;
+    tf::move_resource_state::Response {
+        target_state: Some(dynamic_value()),
+        diagnostics: diagnostics(),
+        target_private: Vec::new(),
+    }
+}
+
+fn read_data_source(request: tf::read_data_source::Request) -> tf::read_data_source::Response {
+    let tf::read_data_source::Request {
+        type_name: _,
+        config: _,
+        provider_meta: _,
+    } = request;
+    tf::read_data_source::Response {
+        state: Some(dynamic_value()),
+        diagnostics: diagnostics(),
+    }
+}
+
+fn get_functions(request: tf::get_functions::Request) -> tf::get_functions::Response {
+    let tf::get_functions::Request {} = request;
+    tf::get_functions::Response {
+        functions: HashMap::from([(String::new(), function())]),
+        diagnostics: diagnostics(),
+    }
+}
+
+fn call_function(request: tf::call_function::Request) -> tf::call_function::Response {
+    let tf::call_function::Request {
+        name: _,
+        arguments: _,
+    } = request;
+    tf::call_function::Response {
+        result: Some(dynamic_value()),
+        error: Some(function_error()),
+    }
+}
+
+fn stop_provider(request: tf::stop_provider::Request) -> tf::stop_provider::Response {
+    let tf::stop_provider::Request {} = request;
+    tf::stop_provider::Response {
+        error: String::new(),
+    }
+}
@@ -17,16 +17,19 @@
 //! [`Value`] module
 
 use std::{
-    borrow::Cow,
+    borrow::{Borrow, Cow},
     collections::{BTreeMap, BTreeSet},
     fmt::{Debug, Display},
+    future::Future,
     iter::FusedIterator,
     mem,
     ops::{Deref, DerefMut},
 };
 
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
+use crate::attribute_path::{AttributePath, AttributePathStep};
+use crate::diagnostics::Diagnostics;
 use crate::utils::serde_unknown;
 
 /// Encode either a known value, a null value, or an unknown value as specified by the Terraform protocol.
@@ -37,6 +40,39 @@ use crate::utils::serde_unknown;
 /// - [`Value::Unknown`] has no option counterpart and represent a value that is currently unknown, but will be known later on.
 ///
 /// [`Value::Unknown`] is *not* a [`Future`](std::future::Future), but merely a tag.
+///
+/// # Examples
+///
+/// Values can be used within `#[serde(flatten)]` blocks, including unknown values:
+///
+/// ```
+/// # use serde::{Deserialize, Serialize};
+/// # use tf_provider::value::{Value, ValueNumber, ValueString};
+/// # use tf_provider::{Diagnostics, RawValue};
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Connect {
+///     host: ValueString<'static>,
+///     port: ValueNumber,
+/// }
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct State {
+///     id: ValueString<'static>,
+///     #[serde(flatten)]
+///     connect: Connect,
+/// }
+///
+/// let mut diags = Diagnostics::default();
+/// for state in [
+///     State { id: Value::Unknown, connect: Connect { host: "localhost".into(), port: Value::Unknown } },
+///     State { id: "id".into(), connect: Connect { host: Value::Unknown, port: Value::Null } },
+///     State { id: Value::Null, connect: Connect { host: Value::Null, port: 22.into() } },
+/// ] {
+///     let raw = RawValue::serialize(&mut diags, &state).unwrap();
+///     assert_eq!(raw.deserialize::<State>(&mut diags), Some(state));
+/// }
+/// assert!(diags.errors.is_empty());
+/// ```
 #[derive(Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Hash, Default, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Value<T> {
@@ -50,20 +86,275 @@ pub enum Value<T> {
     Unknown,
 }
 
+/// Value of any type
+///
+/// # Remarks
+///
+/// Values of dynamic attributes (see [`AttributeType::Any`](crate::schema::AttributeType::Any))
+/// are encoded by Terraform as a `[type, value]` pair, where the type is binary encoded json,
+/// or as a `{"value": value, "type": type}` object in json.
+/// Such values are decoded into a [`ValueAny::Dynamic`] that keeps the type,
+/// and are encoded back the same way.
+///
+/// Numbers that cannot be encoded as native numbers are encoded as strings (see [`Number`]).
+/// Without a type, such strings are decoded as [`ValueAny::String`]:
+/// they are only decoded as [`ValueAny::Number`] inside a [`ValueAny::Dynamic`] whose type is a number.
+/// Use [`ValueNumber`] to decode numbers of typed attributes.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::{Deserialize, Serialize};
+/// # use serde_json::json;
+/// # use tf_provider::{value::ValueAny, Diagnostics, RawValue};
+/// #[derive(Serialize)]
+/// struct Encoded<'a> {
+///     config: (&'a serde_bytes::Bytes, ValueAny),
+///     tuple: ValueAny,
+/// }
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Decoded {
+///     config: ValueAny,
+///     tuple: ValueAny,
+/// }
+///
+/// let object = ValueAny::Map([
+///     ("name".to_owned(), ValueAny::String("foo".into())),
+///     ("count".to_owned(), ValueAny::Number(3.into())),
+/// ].into());
+/// let tuple = ValueAny::List(vec![ValueAny::String("a".into()), ValueAny::Number(1.into())]);
+///
+/// let mut diags = Diagnostics::default();
+/// let raw = RawValue::serialize(&mut diags, &Encoded {
+///     config: (serde_bytes::Bytes::new(br#"["object",{"count":"number","name":"string"}]"#), object.clone()),
+///     tuple: tuple.clone(),
+/// }).unwrap();
+/// let decoded: Decoded = raw.deserialize(&mut diags).unwrap();
+/// assert_eq!(decoded, Decoded {
+///     config: ValueAny::Dynamic(json!(["object", {"count": "number", "name": "string"}]), Box::new(object)),
+///     tuple: tuple.clone(),
+/// });
+///
+/// // Dynamic values are encoded back with their type
+/// assert_eq!(RawValue::serialize(&mut diags, &decoded), Some(raw));
+///
+/// // Json encoding of dynamic values
+/// let json = br#"{"config":{"value":{"count":3,"name":"foo"},"type":["object",{"count":"number","name":"string"}]},"tuple":["a",1]}"#;
+/// let raw = RawValue::Json(json.to_vec());
+/// assert_eq!(raw.deserialize(&mut diags).as_ref(), Some(&decoded));
+/// assert_eq!(serde_json::to_vec(&decoded).unwrap(), json);
+///
+/// // Objects that merely look like a json dynamic value are kept as is
+/// let object = ValueAny::try_from(json!({"value": 1, "type": "hostname"})).unwrap();
+/// assert!(matches!(object, ValueAny::Map(_)));
+/// assert!(diags.errors.is_empty());
+/// ```
 #[derive(Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
-#[serde(untagged)]
+#[serde(untagged, from = "ValueAnyRepr")]
 pub enum ValueAny {
     String(String),
-    Number(i64),
+    Number(Number),
     Bool(bool),
     List(Vec<ValueAny>),
     Map(BTreeMap<String, ValueAny>),
+    /// Value of a dynamic attribute, along with the json representation of its type
+    #[serde(serialize_with = "serialize_dynamic")]
+    Dynamic(serde_json::Value, Box<ValueAny>),
     #[default]
     Null,
     #[serde(with = "serde_unknown")]
     Unknown,
 }
 
+/// Deserialization representation of [`ValueAny`], recognizing dynamic values
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ValueAnyRepr {
+    String(String),
+    Number(Number),
+    Bool(bool),
+    Dynamic(DynamicType, Box<ValueAny>),
+    List(Vec<ValueAny>),
+    DynamicJson(DynamicJson),
+    Map(BTreeMap<String, ValueAny>),
+    Null,
+    #[serde(with = "serde_unknown")]
+    Unknown,
+}
+
+impl From<ValueAnyRepr> for ValueAny {
+    fn from(value: ValueAnyRepr) -> Self {
+        match value {
+            ValueAnyRepr::String(value) => Self::String(value),
+            ValueAnyRepr::Number(value) => Self::Number(value),
+            ValueAnyRepr::Bool(value) => Self::Bool(value),
+            ValueAnyRepr::Dynamic(DynamicType(typ), value) => {
+                let value = Box::new(value.with_type(&typ));
+                Self::Dynamic(typ, value)
+            }
+            ValueAnyRepr::List(value) => Self::List(value),
+            ValueAnyRepr::DynamicJson(DynamicJson { value, typ }) => {
+                let value = Box::new(value.with_type(&typ));
+                Self::Dynamic(typ, value)
+            }
+            ValueAnyRepr::Map(value) => Self::Map(value),
+            ValueAnyRepr::Null => Self::Null,
+            ValueAnyRepr::Unknown => Self::Unknown,
+        }
+    }
+}
+
+impl ValueAny {
+    /// Decode the numbers encoded as strings, according to the json representation of the type of the value
+    fn with_type(self, typ: &serde_json::Value) -> Self {
+        use serde_json::Value as Json;
+        match (self, typ) {
+            (Self::String(value), Json::String(typ)) if typ == "number" => {
+                match Number::parse(&value) {
+                    Some(number) => Self::Number(number),
+                    None => Self::String(value),
+                }
+            }
+            (Self::List(values), Json::Array(typ)) => match typ.as_slice() {
+                [Json::String(kind), element] if matches!(kind.as_str(), "list" | "set") => {
+                    Self::List(values.into_iter().map(|v| v.with_type(element)).collect())
+                }
+                [Json::String(kind), Json::Array(elements)] if kind == "tuple" => Self::List(
+                    values
+                        .into_iter()
+                        .zip(elements.iter().chain(std::iter::repeat(&Json::Null)))
+                        .map(|(value, element)| value.with_type(element))
+                        .collect(),
+                ),
+                _ => Self::List(values),
+            },
+            (Self::Map(values), Json::Array(typ)) => match typ.as_slice() {
+                [Json::String(kind), element] if kind == "map" => Self::Map(
+                    values
+                        .into_iter()
+                        .map(|(key, value)| (key, value.with_type(element)))
+                        .collect(),
+                ),
+                [Json::String(kind), Json::Object(attributes), ..] if kind == "object" => {
+                    Self::Map(
+                        values
+                            .into_iter()
+                            .map(|(key, value)| {
+                                let value = match attributes.get(&key) {
+                                    Some(typ) => value.with_type(typ),
+                                    None => value,
+                                };
+                                (key, value)
+                            })
+                            .collect(),
+                    )
+                }
+                _ => Self::Map(values),
+            },
+            (value, _) => value,
+        }
+    }
+}
+
+/// Serialize a dynamic value as a `[type, value]` pair, or as a `{"value": value, "type": type}` object in json
+fn serialize_dynamic<S>(
+    typ: &serde_json::Value,
+    value: &ValueAny,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    if serializer.is_human_readable() {
+        #[derive(Serialize)]
+        struct DynamicJson<'a> {
+            value: &'a ValueAny,
+            #[serde(rename = "type")]
+            typ: &'a serde_json::Value,
+        }
+        DynamicJson { value, typ }.serialize(serializer)
+    } else {
+        let typ = serde_json::to_vec(typ).map_err(serde::ser::Error::custom)?;
+        (serde_bytes::Bytes::new(&typ), value).serialize(serializer)
+    }
+}
+
+/// Json encoding of a dynamic value
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DynamicJson {
+    value: Box<ValueAny>,
+    #[serde(rename = "type", deserialize_with = "deserialize_type")]
+    typ: serde_json::Value,
+}
+
+/// Deserialize the json representation of a type, rejecting anything that is not a type
+fn deserialize_type<'de, D>(deserializer: D) -> Result<serde_json::Value, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let typ = serde_json::Value::deserialize(deserializer)?;
+    if is_type(&typ) {
+        Ok(typ)
+    } else {
+        Err(serde::de::Error::custom(format!("invalid type: {typ}")))
+    }
+}
+
+/// Check if `typ` is the json representation of a Terraform type
+fn is_type(typ: &serde_json::Value) -> bool {
+    use serde_json::Value as Json;
+    match typ {
+        Json::String(typ) => matches!(typ.as_str(), "string" | "number" | "bool" | "dynamic"),
+        Json::Array(typ) => match typ.as_slice() {
+            [Json::String(kind), element] if matches!(kind.as_str(), "list" | "set" | "map") => {
+                is_type(element)
+            }
+            [Json::String(kind), Json::Array(elements)] if kind == "tuple" => {
+                elements.iter().all(is_type)
+            }
+            [Json::String(kind), Json::Object(attributes), optionals @ ..] if kind == "object" => {
+                attributes.values().all(is_type)
+                    && match optionals {
+                        [] => true,
+                        [Json::Array(optionals)] => optionals.iter().all(Json::is_string),
+                        _ => false,
+                    }
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Type of a dynamic value, binary encoded as json
+///
+/// Only binary data is accepted, so that lists starting with a string are not mistaken for dynamic values.
+struct DynamicType(serde_json::Value);
+
+impl<'de> Deserialize<'de> for DynamicType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct DynamicTypeVisitor;
+        impl<'de> serde::de::Visitor<'de> for DynamicTypeVisitor {
+            type Value = DynamicType;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("binary encoded type")
+            }
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                serde_json::from_slice(v)
+                    .map(DynamicType)
+                    .map_err(E::custom)
+            }
+        }
+        deserializer.deserialize_bytes(DynamicTypeVisitor)
+    }
+}
+
 impl ValueAny {
     /// Dump the json representation of the value
     pub fn json(&self) -> String {
@@ -73,6 +364,563 @@ impl ValueAny {
     pub fn json_pretty(&self) -> String {
         serde_json::to_string_pretty(self).unwrap_or("<invalid>".into())
     }
+
+    /// Get the value wrapped by a dynamic value, or the value itself
+    pub(crate) fn inner(&self) -> &ValueAny {
+        match self {
+            ValueAny::Dynamic(_, value) => value.inner(),
+            value => value,
+        }
+    }
+
+    /// Get a mutable reference to the value wrapped by a dynamic value, or to the value itself
+    pub(crate) fn inner_mut(&mut self) -> &mut ValueAny {
+        match self {
+            ValueAny::Dynamic(_, value) => value.inner_mut(),
+            value => value,
+        }
+    }
+
+    /// Get the value at `path`, if any
+    pub(crate) fn get_path(&self, path: &AttributePath) -> Option<&ValueAny> {
+        path.steps
+            .iter()
+            .try_fold(self, |value, step| match (value.inner(), step) {
+                (ValueAny::Map(map), AttributePathStep::Attribute(name))
+                | (ValueAny::Map(map), AttributePathStep::Key(name)) => map.get(name.as_ref()),
+                (ValueAny::List(list), AttributePathStep::Index(idx)) => {
+                    list.get(usize::try_from(*idx).ok()?)
+                }
+                _ => None,
+            })
+    }
+
+    /// Get a mutable reference to the value at `path`, if any
+    pub(crate) fn get_path_mut(&mut self, path: &AttributePath) -> Option<&mut ValueAny> {
+        path.steps
+            .iter()
+            .try_fold(self, |value, step| match (value.inner_mut(), step) {
+                (ValueAny::Map(map), AttributePathStep::Attribute(name))
+                | (ValueAny::Map(map), AttributePathStep::Key(name)) => map.get_mut(name.as_ref()),
+                (ValueAny::List(list), AttributePathStep::Index(idx)) => {
+                    list.get_mut(usize::try_from(*idx).ok()?)
+                }
+                _ => None,
+            })
+    }
+
+    /// Recursively merge `other` into `self`
+    ///
+    /// Maps are merged key by key, and lists are merged according to `lists`.
+    /// On any other conflict, `other` wins.
+    /// If either side is unknown, and the result depends on it, the result is unknown.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - value merged on top of `self`
+    /// * `lists` - how to merge two lists
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde_json::json;
+    /// # use tf_provider::value::{ListMerge, ValueAny};
+    /// let a = ValueAny::try_from(json!({
+    ///     "name": "a",
+    ///     "tags": ["x"],
+    ///     "nested": {"left": 1, "both": {"left": 1}},
+    /// })).unwrap();
+    /// let b = ValueAny::try_from(json!({
+    ///     "name": "b",
+    ///     "tags": ["y"],
+    ///     "nested": {"right": 2, "both": {"right": 2}},
+    ///     "extra": true,
+    /// })).unwrap();
+    ///
+    /// assert_eq!(a.clone().deep_merge(b.clone(), ListMerge::Replace), ValueAny::try_from(json!({
+    ///     "name": "b",
+    ///     "tags": ["y"],
+    ///     "nested": {"left": 1, "right": 2, "both": {"left": 1, "right": 2}},
+    ///     "extra": true,
+    /// })).unwrap());
+    ///
+    /// assert_eq!(a.deep_merge(b, ListMerge::Concat), ValueAny::try_from(json!({
+    ///     "name": "b",
+    ///     "tags": ["x", "y"],
+    ///     "nested": {"left": 1, "right": 2, "both": {"left": 1, "right": 2}},
+    ///     "extra": true,
+    /// })).unwrap());
+    ///
+    /// // Unknown values are propagated when they could change the result
+    /// let a = ValueAny::try_from(json!({"left": 1})).unwrap();
+    /// assert_eq!(a.clone().deep_merge(ValueAny::Unknown, ListMerge::Replace), ValueAny::Unknown);
+    /// assert_eq!(ValueAny::Unknown.deep_merge(a.clone(), ListMerge::Replace), ValueAny::Unknown);
+    /// assert_eq!(ValueAny::Unknown.deep_merge(ValueAny::Bool(true), ListMerge::Replace), ValueAny::Bool(true));
+    /// ```
+    pub fn deep_merge(self, other: ValueAny, lists: ListMerge) -> ValueAny {
+        match (self, other) {
+            (ValueAny::Map(mut lhs), ValueAny::Map(rhs)) => {
+                for (key, rhs) in rhs {
+                    let merged = match lhs.remove(&key) {
+                        Some(lhs) => lhs.deep_merge(rhs, lists),
+                        None => rhs,
+                    };
+                    lhs.insert(key, merged);
+                }
+                ValueAny::Map(lhs)
+            }
+            (ValueAny::List(mut lhs), ValueAny::List(rhs)) if lists == ListMerge::Concat => {
+                lhs.extend(rhs);
+                ValueAny::List(lhs)
+            }
+            (ValueAny::Unknown, ValueAny::Map(_)) => ValueAny::Unknown,
+            (ValueAny::Unknown, ValueAny::List(_)) if lists == ListMerge::Concat => {
+                ValueAny::Unknown
+            }
+            (_, rhs) => rhs,
+        }
+    }
+}
+
+/// How lists are merged by [`ValueAny::deep_merge`]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub enum ListMerge {
+    /// The list on the right replaces the list on the left
+    #[default]
+    Replace,
+    /// The list on the right is appended to the list on the left
+    Concat,
+}
+
+impl<T: Into<ValueAny>> From<Value<T>> for ValueAny {
+    /// Convert a typed value into a [`ValueAny`]
+    ///
+    /// [`Value::Null`] and [`Value::Unknown`] are converted into [`ValueAny::Null`] and [`ValueAny::Unknown`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::value::{ValueAny, ValueBool, ValueMap, ValueNumber, ValueString};
+    /// assert_eq!(ValueAny::from(ValueString::from("out")), ValueAny::String("out".into()));
+    /// assert_eq!(ValueAny::from(ValueNumber::from(42)), ValueAny::Number(42.into()));
+    /// assert_eq!(ValueAny::from(ValueBool::from(true)), ValueAny::Bool(true));
+    /// assert_eq!(ValueAny::from(ValueString::Null), ValueAny::Null);
+    /// assert_eq!(ValueAny::from(ValueNumber::Unknown), ValueAny::Unknown);
+    ///
+    /// // Heterogeneous map
+    /// let outputs: ValueMap<ValueAny> = [
+    ///     ("stdout", ValueAny::from(ValueString::from("done"))),
+    ///     ("exit_code", ValueNumber::from(0).into()),
+    ///     ("success", ValueBool::from(true).into()),
+    ///     ("stderr", ValueString::Null.into()),
+    ///     ("pid", ValueNumber::Unknown.into()),
+    /// ]
+    /// .into_iter()
+    /// .collect();
+    /// let outputs = outputs.unwrap();
+    /// assert_eq!(outputs["stdout"], ValueAny::String("done".into()));
+    /// assert_eq!(outputs["exit_code"], ValueAny::Number(0.into()));
+    /// assert_eq!(outputs["success"], ValueAny::Bool(true));
+    /// assert_eq!(outputs["stderr"], ValueAny::Null);
+    /// assert_eq!(outputs["pid"], ValueAny::Unknown);
+    /// ```
+    fn from(value: Value<T>) -> Self {
+        match value {
+            Value::Value(value) => value.into(),
+            Value::Null => ValueAny::Null,
+            Value::Unknown => ValueAny::Unknown,
+        }
+    }
+}
+
+impl From<String> for ValueAny {
+    fn from(value: String) -> Self {
+        ValueAny::String(value)
+    }
+}
+
+impl From<&str> for ValueAny {
+    fn from(value: &str) -> Self {
+        ValueAny::String(value.to_owned())
+    }
+}
+
+impl<'a> From<Cow<'a, str>> for ValueAny {
+    fn from(value: Cow<'a, str>) -> Self {
+        ValueAny::String(value.into_owned())
+    }
+}
+
+impl From<bool> for ValueAny {
+    fn from(value: bool) -> Self {
+        ValueAny::Bool(value)
+    }
+}
+
+impl From<Number> for ValueAny {
+    fn from(value: Number) -> Self {
+        ValueAny::Number(value)
+    }
+}
+
+macro_rules! impl_value_any_from_number {
+    ($($t:ty)*) => {$(
+        impl From<$t> for ValueAny {
+            fn from(value: $t) -> Self {
+                ValueAny::Number(value.into())
+            }
+        }
+    )*};
+}
+impl_value_any_from_number!(i8 i16 i32 i64 i128 u8 u16 u32 u64 f32 f64);
+
+impl<'a, K, V> FromIterator<(K, V)> for Value<BTreeMap<Cow<'a, str>, ValueAny>>
+where
+    K: Into<Cow<'a, str>>,
+    V: Into<ValueAny>,
+{
+    /// Build a map of heterogeneous values
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Value::Value(
+            iter.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+impl TryFrom<serde_json::Value> for ValueAny {
+    type Error = serde_json::Error;
+
+    /// Convert a json value into a [`ValueAny`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::value::ValueAny;
+    /// # use serde_json::json;
+    /// let value = ValueAny::try_from(json!({
+    ///     "name": "foo",
+    ///     "count": 3,
+    ///     "tags": ["a", "b"],
+    ///     "parent": null,
+    /// })).unwrap();
+    ///
+    /// let ValueAny::Map(map) = value else { panic!("expected a map") };
+    /// assert_eq!(map["name"], ValueAny::String("foo".into()));
+    /// assert_eq!(map["count"], ValueAny::Number(3.into()));
+    /// assert_eq!(map["tags"], ValueAny::List(vec![ValueAny::String("a".into()), ValueAny::String("b".into())]));
+    /// assert_eq!(map["parent"], ValueAny::Null);
+    /// ```
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        serde_json::from_value(value)
+    }
+}
+
+/// Arbitrary precision number
+///
+/// Terraform numbers are arbitrary precision.
+/// Integers are stored as [`i128`], other numbers are stored as their decimal representation.
+///
+/// Numbers that do not fit in an [`i64`] nor a [`u64`], and decimals that are not exactly represented by an [`f64`],
+/// are serialized as strings, as Terraform does in its Message Pack encoding.
+/// For instance, `0.1` is sent as a string, as the closest [`f64`] is `0.1000000000000000055511151231257827…`.
+/// In json, a decimal is only serialized as a string if its [`f64`] is not written as the same text.
+/// Such strings are decoded back into a [`Number`], but not into a [`ValueAny::Number`]
+/// unless they are part of a [`ValueAny::Dynamic`] whose type is a number.
+///
+/// # Examples
+///
+/// ```
+/// # use tf_provider::value::{Number, Value, ValueNumber};
+/// let n: Number = serde_json::from_str("18446744073709551615").unwrap();
+/// assert_eq!(n, Number::Int(u64::MAX as i128));
+/// assert_eq!(n.as_i64(), None);
+/// assert_eq!(n.as_u64(), Some(u64::MAX));
+///
+/// let n: ValueNumber = serde_json::from_str("18446744073709551615").unwrap();
+/// assert_eq!(n, ValueNumber::from(u64::MAX));
+///
+/// // Large numbers are sent by Terraform as strings
+/// let n: Value<Number> = serde_json::from_str("\"1267650600228229401496703205376\"").unwrap();
+/// assert_eq!(n, Value::Value(Number::Int(1 << 100)));
+///
+/// let n: Number = serde_json::from_str("0.5").unwrap();
+/// assert_eq!(n, Number::Decimal("0.5".into()));
+/// assert_eq!(n.as_f64(), Some(0.5));
+///
+/// // Terraform sends numbers that are neither an exact int64 nor an exact float64 as Message Pack strings
+/// # use tf_provider::{value::ValueAny, Diagnostics, RawValue};
+/// # use serde_json::json;
+/// let mut diags = Diagnostics::default();
+/// let mut bytes = vec![0xb4]; // string of 20 bytes
+/// bytes.extend_from_slice(b"18446744073709551615");
+/// let raw = RawValue::MessagePack(bytes.clone());
+/// assert_eq!(raw.deserialize::<ValueNumber>(&mut diags), Some(ValueNumber::from(u64::MAX)));
+/// // Without a type, the string is kept as is
+/// assert_eq!(raw.deserialize::<ValueAny>(&mut diags), Some(ValueAny::String("18446744073709551615".into())));
+///
+/// // With the type of a dynamic value, the string is decoded as a number
+/// let mut dynamic = vec![0x92, 0xc4, 17]; // `[type, value]` pair, with the type as 17 bytes
+/// dynamic.extend_from_slice(br#"["list","number"]"#);
+/// dynamic.extend_from_slice(&[0x92, 0xa3]); // list of 2 elements, string of 3 bytes
+/// dynamic.extend_from_slice(b"0.1");
+/// dynamic.extend_from_slice(&bytes);
+/// let raw = RawValue::MessagePack(dynamic);
+/// let value = ValueAny::Dynamic(json!(["list", "number"]), Box::new(ValueAny::List(vec![
+///     ValueAny::Number(Number::Decimal("0.1".into())),
+///     ValueAny::Number(Number::Int(u64::MAX as i128)),
+/// ])));
+/// assert_eq!(raw.deserialize::<ValueAny>(&mut diags), Some(value));
+///
+/// // Strings of the user are never decoded as numbers
+/// let value = ValueAny::String("99999999999999999999999".into());
+/// let raw = RawValue::serialize(&mut diags, &value).unwrap();
+/// assert_eq!(raw.deserialize::<ValueAny>(&mut diags), Some(value));
+///
+/// // Numbers survive a round-trip, including the ones serialized as strings
+/// for n in [
+///     Number::Int(i64::MAX as i128 + 1),
+///     Number::Int(u64::MAX as i128),
+///     Number::Int(1 << 100),
+///     Number::Int(-1 << 100),
+///     Number::Decimal("3.14".into()),
+///     Number::Decimal("0.10000000000000000000001".into()),
+///     Number::Decimal("1e400".into()),
+/// ] {
+///     let value = ValueNumber::Value(n.clone());
+///     for raw in [
+///         RawValue::serialize(&mut diags, &value).unwrap(),
+///         RawValue::Json(serde_json::to_vec(&value).unwrap()),
+///     ] {
+///         assert_eq!(raw.deserialize::<ValueNumber>(&mut diags), Some(value.clone()));
+///     }
+///     let value = ValueAny::Dynamic(json!("number"), Box::new(ValueAny::Number(n)));
+///     let raw = RawValue::serialize(&mut diags, &value).unwrap();
+///     assert_eq!(raw.deserialize::<ValueAny>(&mut diags), Some(value));
+/// }
+///
+/// // Decimals are sent as floats only if they are exactly represented
+/// for (n, bytes) in [
+///     ("0.1", vec![0xa3, b'0', b'.', b'1']),
+///     ("0.5", vec![0xcb, 0x3f, 0xe0, 0, 0, 0, 0, 0, 0]),
+///     ("-2.5e-1", vec![0xcb, 0xbf, 0xd0, 0, 0, 0, 0, 0, 0]),
+/// ] {
+///     let value = ValueAny::Number(Number::Decimal(n.into()));
+///     assert_eq!(RawValue::serialize(&mut diags, &value), Some(RawValue::MessagePack(bytes)));
+/// }
+/// assert_eq!(serde_json::to_string(&Number::Decimal("0.1".into())).unwrap(), "0.1");
+///
+/// // Floats are converted into their shortest exact decimal representation
+/// assert_eq!(Number::from(1.5), Number::Decimal("1.5".into()));
+/// assert_eq!(Number::from(3.0), Number::Int(3));
+/// assert_eq!(ValueAny::from(0.1), ValueAny::Number(Number::Decimal("0.1".into())));
+/// assert!(!diags.has_errors());
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Number {
+    /// Integer
+    Int(i128),
+    /// Non-integer number in decimal representation
+    Decimal(String),
+}
+
+impl Number {
+    /// Get the number as an [`i64`] if it fits
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Int(i) => i64::try_from(*i).ok(),
+            Self::Decimal(_) => None,
+        }
+    }
+    /// Get the number as an [`u64`] if it fits
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Self::Int(i) => u64::try_from(*i).ok(),
+            Self::Decimal(_) => None,
+        }
+    }
+    /// Get the number as an [`i128`] if it is an integer
+    pub fn as_i128(&self) -> Option<i128> {
+        match self {
+            Self::Int(i) => Some(*i),
+            Self::Decimal(_) => None,
+        }
+    }
+    /// Get the number as an [`f64`]
+    ///
+    /// The conversion may lose precision.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Int(i) => Some(*i as f64),
+            Self::Decimal(d) => d.parse().ok(),
+        }
+    }
+
+    /// Parse a number from its decimal representation
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        if let Ok(i) = s.parse() {
+            Some(Self::Int(i))
+        } else if s.parse::<f64>().is_ok()
+            && s.bytes()
+                .all(|c| c.is_ascii_digit() || b"+-.eE".contains(&c))
+        {
+            Some(Self::Decimal(s.to_owned()))
+        } else {
+            None
+        }
+    }
+
+    /// Check if the number cannot be serialized as a native number without loss
+    ///
+    /// In Message Pack, a decimal is a native number only if an [`f64`] represents it exactly.
+    /// In json, native numbers are written as text, so it is enough that the text of the [`f64`] is the decimal.
+    fn is_serialized_as_string(&self, human_readable: bool) -> bool {
+        match self {
+            Self::Int(i) => i64::try_from(*i).is_err() && u64::try_from(*i).is_err(),
+            Self::Decimal(d) => !matches!(
+                d.parse::<f64>(),
+                Ok(f) if f.is_finite() && if human_readable {
+                    f.to_string() == *d
+                } else {
+                    // The exact binary expansion of an f64 has at most 1074 fractional digits
+                    decimal_parts(d) == decimal_parts(&format!("{f:.1074}"))
+                }
+            ),
+        }
+    }
+}
+
+/// Split a decimal representation into its sign, significant digits, and exponent
+///
+/// The parts are canonical: equal numbers have equal parts, whatever their representation.
+fn decimal_parts(s: &str) -> Option<(bool, String, i64)> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (mantissa, exponent) = match s.find(['e', 'E']) {
+        Some(i) => (&s[..i], s[i + 1..].parse::<i64>().ok()?),
+        None => (s, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if !int.bytes().chain(frac.bytes()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let digits = format!("{int}{frac}");
+    let significant = digits.trim_end_matches('0');
+    let exponent = exponent - frac.len() as i64 + (digits.len() - significant.len()) as i64;
+    let significant = significant.trim_start_matches('0');
+    if significant.is_empty() {
+        Some((false, String::new(), 0))
+    } else {
+        Some((negative, significant.to_owned(), exponent))
+    }
+}
+
+impl Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Int(i) => Display::fmt(i, f),
+            Self::Decimal(d) => f.write_str(d),
+        }
+    }
+}
+
+macro_rules! impl_number_from {
+    ($($t:ty)*) => {$(
+        impl From<$t> for Number {
+            fn from(value: $t) -> Self {
+                Self::Int(value as i128)
+            }
+        }
+    )*};
+}
+impl_number_from!(i8 i16 i32 i64 i128 u8 u16 u32 u64);
+
+impl From<f64> for Number {
+    fn from(value: f64) -> Self {
+        if value.fract() == 0.0 && value.abs() < i128::MAX as f64 {
+            Self::Int(value as i128)
+        } else {
+            Self::Decimal(value.to_string())
+        }
+    }
+}
+
+impl From<f32> for Number {
+    fn from(value: f32) -> Self {
+        Self::from(value as f64)
+    }
+}
+
+macro_rules! impl_value_number_from {
+    ($($t:ty)*) => {$(
+        impl From<$t> for ValueNumber {
+            fn from(value: $t) -> Self {
+                Self::Value(value.into())
+            }
+        }
+    )*};
+}
+impl_value_number_from!(i8 i16 i32 i64 i128 u8 u16 u32 u64 f32 f64);
+
+impl Serialize for Number {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if self.is_serialized_as_string(serializer.is_human_readable()) {
+            return serializer.collect_str(self);
+        }
+        match self {
+            Self::Int(i) => match i64::try_from(*i) {
+                Ok(i) => serializer.serialize_i64(i),
+                Err(_) => serializer.serialize_u64(*i as u64),
+            },
+            Self::Decimal(d) => serializer.serialize_f64(d.parse().unwrap_or_default()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Number {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct NumberVisitor;
+        impl<'de> serde::de::Visitor<'de> for NumberVisitor {
+            type Value = Number;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a number")
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(Number::Int(v as i128))
+            }
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(Number::Int(v as i128))
+            }
+            fn visit_i128<E: serde::de::Error>(self, v: i128) -> Result<Self::Value, E> {
+                Ok(Number::Int(v))
+            }
+            fn visit_u128<E: serde::de::Error>(self, v: u128) -> Result<Self::Value, E> {
+                match i128::try_from(v) {
+                    Ok(v) => Ok(Number::Int(v)),
+                    Err(_) => Ok(Number::Decimal(v.to_string())),
+                }
+            }
+            fn visit_f64<E: serde::de::Error>(self, v: f64) -> Result<Self::Value, E> {
+                Ok(Number::from(v))
+            }
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Number::parse(v)
+                    .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+            }
+        }
+        deserializer.deserialize_any(NumberVisitor)
+    }
 }
 
 /// Struct without any field
@@ -82,8 +930,27 @@ impl ValueAny {
 pub struct StructEmpty {}
 
 pub type ValueEmpty = Value<StructEmpty>;
+/// String value
+///
+/// Equality and hashing do not depend on whether the string is borrowed or owned.
+///
+/// # Examples
+///
+/// ```
+/// # use std::borrow::Cow;
+/// # use std::collections::HashSet;
+/// # use tf_provider::value::{Value, ValueString};
+/// let owned: ValueString = Value::Value(Cow::Owned("cmd".to_owned()));
+/// let borrowed: ValueString = Value::Value(Cow::Borrowed("cmd"));
+/// assert_eq!(owned, borrowed);
+///
+/// let mut modified = HashSet::new();
+/// assert!(modified.insert(owned));
+/// assert!(!modified.insert(borrowed));
+/// assert_eq!(modified.len(), 1);
+/// ```
 pub type ValueString<'a> = Value<Cow<'a, str>>;
-pub type ValueNumber = Value<i64>;
+pub type ValueNumber = Value<Number>;
 pub type ValueBool = Value<bool>;
 pub type ValueList<T> = Value<Vec<T>>;
 pub type ValueSet<T> = Value<BTreeSet<T>>;
@@ -130,6 +997,71 @@ pub mod serde_as_vec {
     }
 }
 
+/// Returns the first value that is not null
+///
+/// If an unknown value comes before the first known value, the result is unknown,
+/// as the unknown value might become the winner once it is known.
+/// If all the values are null, or if there is no value, the result is null.
+///
+/// This is equivalent to chaining [`Value::or`] over all the values.
+///
+/// # Arguments
+///
+/// * `values` - candidates, ordered by priority
+///
+/// # Examples
+///
+/// ```
+/// # use tf_provider::value::{coalesce, Value};
+/// assert_eq!(coalesce([Value::Null, Value::Value(1), Value::Value(2)]), Value::Value(1));
+/// assert_eq!(coalesce([Value::Value(1), Value::Unknown]), Value::Value(1));
+/// assert_eq!(coalesce([Value::Null, Value::Value(1), Value::Unknown]), Value::Value(1));
+/// assert_eq!(coalesce([Value::Unknown, Value::Value(1)]), Value::Unknown);
+/// assert_eq!(coalesce([Value::Null, Value::Unknown, Value::Value(1)]), Value::Unknown);
+/// assert_eq!(coalesce([Value::Null, Value::Unknown]), Value::<i32>::Unknown);
+/// assert_eq!(coalesce([Value::Null, Value::Null]), Value::<i32>::Null);
+/// assert_eq!(coalesce(Vec::<Value<i32>>::new()), Value::Null);
+/// ```
+pub fn coalesce<T>(values: impl IntoIterator<Item = Value<T>>) -> Value<T> {
+    values
+        .into_iter()
+        .find(|value| !value.is_null())
+        .unwrap_or(Value::Null)
+}
+
+/// Maps a [`Value<T>`] to a [`Value<U>`] by awaiting the future returned by `f` on the contained value
+///
+/// `f` is only called for [`Value::Value`]: [`Value::Null`] and [`Value::Unknown`] are passed through.
+/// This is the asynchronous counterpart of [`Value::map`].
+///
+/// # Arguments
+///
+/// * `value` - value to map
+/// * `f` - asynchronous function applied to the contained value
+///
+/// # Examples
+///
+/// ```
+/// # use tf_provider::value::{map_value, Value};
+/// futures::executor::block_on(async {
+///     let len = |s: &'static str| async move { s.len() };
+///     assert_eq!(map_value(Value::Value("hello"), len).await, Value::Value(5));
+///     assert_eq!(map_value(Value::Null, len).await, Value::Null);
+///     assert_eq!(map_value(Value::Unknown, len).await, Value::Unknown);
+/// });
+/// ```
+pub async fn map_value<T, U, F, Fut>(value: Value<T>, f: F) -> Value<U>
+where
+    F: FnOnce(T) -> Fut,
+    Fut: Future<Output = U>,
+{
+    match value {
+        Value::Value(x) => Value::Value(f(x).await),
+        Value::Null => Value::Null,
+        Value::Unknown => Value::Unknown,
+    }
+}
+
 impl<T> Value<T> {
     /////////////////////////////////////////////////////////////////////////
     // Querying the contained values
@@ -153,6 +1085,21 @@ impl<T> Value<T> {
         matches!(self, Self::Unknown)
     }
 
+    /// Check if the value is known, ie: either present or null
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::value::Value;
+    /// assert!(Value::Value(2).is_known());
+    /// assert!(Value::<u32>::Null.is_known());
+    /// assert!(!Value::<u32>::Unknown.is_known());
+    /// ```
+    #[inline]
+    pub const fn is_known(&self) -> bool {
+        !self.is_unknown()
+    }
+
     /////////////////////////////////////////////////////////////////////////
     // Adapter for working with references
     /////////////////////////////////////////////////////////////////////////
@@ -287,6 +1234,42 @@ impl<T> Value<T> {
         }
     }
 
+    /// Returns the contained [`Value::Value`] value, consuming the `self` value,
+    /// without checking that the value is not [`Value::Null`] or [`Value::Unknown`].
+    ///
+    /// # Safety
+    ///
+    /// Calling this method on [`Value::Null`] or [`Value::Unknown`] is *[undefined behavior]*.
+    /// In debug builds, it panics instead.
+    ///
+    /// [undefined behavior]: https://doc.rust-lang.org/reference/behavior-considered-undefined.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::value::Value;
+    /// let x = Value::Value("value");
+    /// if x.is_value() {
+    ///     assert_eq!(unsafe { x.unwrap_unchecked() }, "value");
+    /// }
+    /// ```
+    ///
+    /// ```no_run
+    /// # use tf_provider::value::Value;
+    /// let x: Value<&str> = Value::Null;
+    /// assert_eq!(unsafe { x.unwrap_unchecked() }, "value"); // Undefined behavior!
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub unsafe fn unwrap_unchecked(self) -> T {
+        debug_assert!(self.is_value());
+        match self {
+            Self::Value(x) => x,
+            // SAFETY: the safety contract must be upheld by the caller
+            _ => unsafe { std::hint::unreachable_unchecked() },
+        }
+    }
+
     /// Returns the contained Some value or a provided default.
     ///
     /// Arguments passed to `unwrap_or` are eagerly evaluated;
@@ -709,11 +1692,58 @@ impl<T> Value<T> {
     /// assert_eq!(x, Value::Value(42));
     /// ```
     #[inline]
-    pub fn as_mut_option(&mut self) -> Option<&mut T> {
-        match self {
-            Self::Value(x) => Some(x),
-            _ => None,
-        }
+    pub fn as_mut_option(&mut self) -> Option<&mut T> {
+        match self {
+            Self::Value(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the contained value, if any
+    ///
+    /// Equivalent to [`Value::as_ref_option`]: [`Value::Null`] and [`Value::Unknown`] are mapped to [`None`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::value::Value;
+    /// let x: Value<String> = Value::Value("hey".to_owned());
+    /// assert_eq!(x.get(), Some(&"hey".to_owned()));
+    ///
+    /// let x: Value<String> = Value::Null;
+    /// assert_eq!(x.get(), None);
+    ///
+    /// let x: Value<String> = Value::Unknown;
+    /// assert_eq!(x.get(), None);
+    /// ```
+    #[inline]
+    pub fn get(&self) -> Option<&T> {
+        self.as_ref_option()
+    }
+
+    /// Returns a mutable reference to the contained value, if any
+    ///
+    /// Equivalent to [`Value::as_mut_option`]: [`Value::Null`] and [`Value::Unknown`] are mapped to [`None`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::value::Value;
+    /// let mut x = Value::Value(2);
+    /// if let Some(v) = x.get_mut() {
+    ///     *v = 42;
+    /// }
+    /// assert_eq!(x, Value::Value(42));
+    ///
+    /// let mut x: Value<i32> = Value::Null;
+    /// assert_eq!(x.get_mut(), None);
+    ///
+    /// let mut x: Value<i32> = Value::Unknown;
+    /// assert_eq!(x.get_mut(), None);
+    /// ```
+    #[inline]
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        self.as_mut_option()
     }
 
     /// Converts from `Value<T>` (or `&Value<T>`) to `Option<&T::Target>`, mapping [`Value::Value(v)`] to
@@ -1078,6 +2108,27 @@ impl<T> Value<T> {
         }
     }
 
+    /// Replaces an unknown value by `computed`, leaving actual and null values untouched.
+    ///
+    /// This is useful when applying a planned state: unknown attributes must be filled with
+    /// the freshly computed values, while known attributes are forced by the configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::value::Value;
+    /// assert_eq!(Value::Value("planned").fill_unknown("computed"), Value::Value("planned"));
+    /// assert_eq!(Value::Null.fill_unknown("computed"), Value::Null);
+    /// assert_eq!(Value::Unknown.fill_unknown("computed"), Value::Value("computed"));
+    /// ```
+    #[inline]
+    pub fn fill_unknown(self, computed: T) -> Self {
+        match self {
+            Self::Unknown => Value::Value(computed),
+            value => value,
+        }
+    }
+
     /////////////////////////////////////////////////////////////////////////
     // Misc
     /////////////////////////////////////////////////////////////////////////
@@ -1107,6 +2158,50 @@ impl<T> Value<T> {
         mem::replace(self, Value::Null)
     }
 
+    /// Takes the value out, leaving a [`Value::Null`] in its place,
+    /// only if it is present and the predicate evaluates to `true`.
+    ///
+    /// Otherwise, returns [`Value::Null`] and leaves the value untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::value::Value;
+    /// let mut x = Value::Value(42);
+    /// let y = x.take_if(|v| *v == 42);
+    /// assert_eq!(x, Value::Null);
+    /// assert_eq!(y, Value::Value(42));
+    ///
+    /// let mut x = Value::Value(42);
+    /// let y = x.take_if(|v| {
+    ///     *v += 1;
+    ///     false
+    /// });
+    /// assert_eq!(x, Value::Value(43));
+    /// assert_eq!(y, Value::Null);
+    ///
+    /// let mut x: Value<u32> = Value::Null;
+    /// let y = x.take_if(|_| true);
+    /// assert_eq!(x, Value::Null);
+    /// assert_eq!(y, Value::Null);
+    ///
+    /// let mut x: Value<u32> = Value::Unknown;
+    /// let y = x.take_if(|_| true);
+    /// assert_eq!(x, Value::Unknown);
+    /// assert_eq!(y, Value::Null);
+    /// ```
+    #[inline]
+    pub fn take_if<P>(&mut self, predicate: P) -> Self
+    where
+        P: FnOnce(&mut T) -> bool,
+    {
+        if self.as_mut().map_or(false, predicate) {
+            self.take()
+        } else {
+            Value::Null
+        }
+    }
+
     /// Replaces the actual value by the value given in parameter,
     /// returning the old value if present,
     /// leaving a [`Value::Null`] in its place without deinitializing either one.
@@ -1134,6 +2229,70 @@ impl<T> Value<T> {
         mem::replace(self, Value::Value(val))
     }
 
+    /// Inserts `value` if the value is [`Value::Null`] or [`Value::Unknown`],
+    /// then returns a mutable reference to the contained value.
+    ///
+    /// [`Value::Unknown`] is overwritten, as normalization routines replace
+    /// whatever has not been set by an actual value.
+    ///
+    /// See also [`Value::get_or_insert_with`], which lazily computes the inserted value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::value::Value;
+    /// let mut x = Value::Value(2);
+    /// *x.get_or_insert(5) += 1;
+    /// assert_eq!(x, Value::Value(3));
+    ///
+    /// let mut x = Value::Null;
+    /// *x.get_or_insert(5) += 1;
+    /// assert_eq!(x, Value::Value(6));
+    ///
+    /// let mut x = Value::Unknown;
+    /// *x.get_or_insert(5) += 1;
+    /// assert_eq!(x, Value::Value(6));
+    /// ```
+    #[inline]
+    pub fn get_or_insert(&mut self, value: T) -> &mut T {
+        self.get_or_insert_with(|| value)
+    }
+
+    /// Inserts a value computed from `f` if the value is [`Value::Null`] or [`Value::Unknown`],
+    /// then returns a mutable reference to the contained value.
+    ///
+    /// `f` is not called if the value is already present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::value::Value;
+    /// let mut x = Value::Value("mode".to_owned());
+    /// x.get_or_insert_with(|| unreachable!()).push_str("=0644");
+    /// assert_eq!(x, Value::Value("mode=0644".to_owned()));
+    ///
+    /// let mut x: Value<String> = Value::Null;
+    /// x.get_or_insert_with(String::new).push_str("0644");
+    /// assert_eq!(x, Value::Value("0644".to_owned()));
+    ///
+    /// let mut x: Value<String> = Value::Unknown;
+    /// x.get_or_insert_with(String::new).push_str("0644");
+    /// assert_eq!(x, Value::Value("0644".to_owned()));
+    /// ```
+    #[inline]
+    pub fn get_or_insert_with<F>(&mut self, f: F) -> &mut T
+    where
+        F: FnOnce() -> T,
+    {
+        if !self.is_value() {
+            *self = Self::Value(f());
+        }
+        let Self::Value(value) = self else {
+            unreachable!("the value has just been set")
+        };
+        value
+    }
+
     /// Check if the value contains `x`.
     ///
     /// # Examples
@@ -1154,6 +2313,103 @@ impl<T> Value<T> {
             _ => false,
         }
     }
+
+    /// Zips `self` with another [`Value`].
+    ///
+    /// If both values are present, returns a [`Value::Value`] with both values.
+    /// Otherwise, if any of the values is unknown, returns [`Value::Unknown`].
+    /// Otherwise, returns [`Value::Null`].
+    ///
+    /// # Remarks
+    ///
+    /// Contrary to [`Value::and`], the result does not depend on the order of the operands:
+    /// unknown always wins over null, whatever its side.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::value::Value;
+    /// let x = Value::Value(2);
+    /// assert_eq!(x.zip(Value::Value("foo")), Value::Value((2, "foo")));
+    /// assert_eq!(x.zip(Value::<&str>::Null), Value::Null);
+    /// assert_eq!(x.zip(Value::<&str>::Unknown), Value::Unknown);
+    ///
+    /// let x: Value<u32> = Value::Null;
+    /// assert_eq!(x.zip(Value::Value("foo")), Value::Null);
+    /// assert_eq!(x.zip(Value::<&str>::Null), Value::Null);
+    /// assert_eq!(x.zip(Value::<&str>::Unknown), Value::Unknown);
+    ///
+    /// let x: Value<u32> = Value::Unknown;
+    /// assert_eq!(x.zip(Value::Value("foo")), Value::Unknown);
+    /// assert_eq!(x.zip(Value::<&str>::Null), Value::Unknown);
+    /// assert_eq!(x.zip(Value::<&str>::Unknown), Value::Unknown);
+    /// ```
+    #[inline]
+    pub fn zip<U>(self, other: Value<U>) -> Value<(T, U)> {
+        match (self, other) {
+            (Value::Value(x), Value::Value(y)) => Value::Value((x, y)),
+            (Value::Unknown, _) | (_, Value::Unknown) => Value::Unknown,
+            _ => Value::Null,
+        }
+    }
+}
+
+impl<T, U> Value<(T, U)> {
+    /// Unzips a value containing a tuple into a tuple of values.
+    ///
+    /// If the value is [`Value::Null`] or [`Value::Unknown`], both returned values are the same.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::value::Value;
+    /// let x = Value::Value((1, "hi"));
+    /// assert_eq!(x.unzip(), (Value::Value(1), Value::Value("hi")));
+    ///
+    /// let x: Value<(u32, &str)> = Value::Null;
+    /// assert_eq!(x.unzip(), (Value::Null, Value::Null));
+    ///
+    /// let x: Value<(u32, &str)> = Value::Unknown;
+    /// assert_eq!(x.unzip(), (Value::Unknown, Value::Unknown));
+    /// ```
+    #[inline]
+    pub fn unzip(self) -> (Value<T>, Value<U>) {
+        match self {
+            Value::Value((x, y)) => (Value::Value(x), Value::Value(y)),
+            Value::Null => (Value::Null, Value::Null),
+            Value::Unknown => (Value::Unknown, Value::Unknown),
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Value<T> {
+    /// Build a [`Value`] from a json value
+    ///
+    /// A json `null` is converted into [`Value::Null`].
+    /// This is mostly useful to write states as json literals in tests.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde::Deserialize;
+    /// # use serde_json::json;
+    /// use tf_provider::value::{Value, ValueNumber, ValueString};
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    /// struct State {
+    ///     id: ValueString<'static>,
+    ///     size: ValueNumber,
+    /// }
+    ///
+    /// let state = Value::<State>::from_json(json!({"id": "foo", "size": null})).unwrap();
+    /// assert_eq!(state, Value::Value(State { id: "foo".into(), size: Value::Null }));
+    ///
+    /// let state = Value::<State>::from_json(json!(null)).unwrap();
+    /// assert_eq!(state, Value::Null);
+    /// ```
+    pub fn from_json(json: serde_json::Value) -> Result<Self, serde_json::Error> {
+        serde_json::from_value(json)
+    }
 }
 
 impl<T> Value<&T> {
@@ -1309,7 +2565,360 @@ impl<'a> Value<Cow<'a, str>> {
             Value::Unknown => Value::Unknown,
         }
     }
+
+    /// Decode a base64 encoded string
+    ///
+    /// If the value is [`Value::Null`], returns [`Value::Null`].
+    /// If the value is [`Value::Unknown`], returns [`Value::Unknown`].
+    ///
+    /// # Arguments
+    ///
+    /// * `engine` - base64 variant used to decode the string
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+    /// # use tf_provider::value::{Value, ValueString};
+    /// let value: ValueString = "aGk/Pw==".into();
+    /// assert_eq!(value.decode_base64(&STANDARD), Ok(Value::Value(b"hi??".to_vec())));
+    /// assert!(value.decode_base64(&URL_SAFE_NO_PAD).is_err());
+    ///
+    /// let value: ValueString = "aGk_Pw".into();
+    /// assert_eq!(value.decode_base64(&URL_SAFE_NO_PAD), Ok(Value::Value(b"hi??".to_vec())));
+    /// assert!(value.decode_base64(&STANDARD).is_err());
+    ///
+    /// assert_eq!(ValueString::Null.decode_base64(&STANDARD), Ok(Value::Null));
+    /// assert_eq!(ValueString::Unknown.decode_base64(&STANDARD), Ok(Value::Unknown));
+    /// ```
+    pub fn decode_base64<E: base64::Engine>(
+        &self,
+        engine: &E,
+    ) -> Result<Value<Vec<u8>>, base64::DecodeError> {
+        match self {
+            Self::Value(x) => Ok(Value::Value(engine.decode(x.as_bytes())?)),
+            Self::Null => Ok(Value::Null),
+            Self::Unknown => Ok(Value::Unknown),
+        }
+    }
+
+    /// Decode an hexadecimal encoded string
+    ///
+    /// Both lowercase and uppercase digits are accepted.
+    /// If the value is [`Value::Null`], returns [`Value::Null`].
+    /// If the value is [`Value::Unknown`], returns [`Value::Unknown`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::value::{HexDecodeError, Value, ValueString};
+    /// let value: ValueString = "00ff7Fa0".into();
+    /// assert_eq!(value.decode_hex(), Ok(Value::Value(vec![0x00, 0xff, 0x7f, 0xa0])));
+    ///
+    /// let value: ValueString = "abc".into();
+    /// assert_eq!(value.decode_hex(), Err(HexDecodeError::OddLength));
+    ///
+    /// let value: ValueString = "0g".into();
+    /// assert_eq!(value.decode_hex(), Err(HexDecodeError::InvalidCharacter(1, 'g')));
+    ///
+    /// assert_eq!(ValueString::Null.decode_hex(), Ok(Value::Null));
+    /// assert_eq!(ValueString::Unknown.decode_hex(), Ok(Value::Unknown));
+    /// ```
+    pub fn decode_hex(&self) -> Result<Value<Vec<u8>>, HexDecodeError> {
+        let hex = match self {
+            Self::Value(x) => x.as_ref(),
+            Self::Null => return Ok(Value::Null),
+            Self::Unknown => return Ok(Value::Unknown),
+        };
+        if hex.len() % 2 != 0 {
+            return Err(HexDecodeError::OddLength);
+        }
+        let digit = |index: usize, c: u8| {
+            (c as char).to_digit(16).map(|d| d as u8).ok_or_else(|| {
+                let c = hex.get(index..).and_then(|s| s.chars().next());
+                HexDecodeError::InvalidCharacter(index, c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            })
+        };
+        hex.as_bytes()
+            .chunks(2)
+            .enumerate()
+            .map(|(i, pair)| Ok(digit(2 * i, pair[0])? << 4 | digit(2 * i + 1, pair[1])?))
+            .collect::<Result<Vec<u8>, _>>()
+            .map(Value::Value)
+    }
+
+    /// Parse the string into an integer, reporting parsing errors in diagnostics
+    ///
+    /// # Arguments
+    ///
+    /// * `diags` - Diagnostics where parsing errors are reported
+    /// * `path` - Path of the attribute holding the string
+    /// * `radix` - Base of the number, from 2 to 36
+    ///
+    /// # Remarks
+    ///
+    /// Returns [`None`] if the string cannot be parsed, and reports an error on `path`.
+    /// Returns [`None`] without reporting any error if the value is null or unknown.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `radix` is not in the range from 2 to 36.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::value::ValueString;
+    /// # use tf_provider::{AttributePath, Diagnostics};
+    /// let mut diags = Diagnostics::default();
+    /// let path = AttributePath::new("mode");
+    ///
+    /// let mode: ValueString = "0644".into();
+    /// assert_eq!(mode.parse_into_diag::<u32>(&mut diags, path.clone(), 8), Some(0o644));
+    /// assert!(!diags.has_errors());
+    ///
+    /// let mode: ValueString = "0984".into();
+    /// assert_eq!(mode.parse_into_diag::<u32>(&mut diags, path.clone(), 8), None);
+    /// assert_eq!(diags.error_count(), 1);
+    /// assert_eq!(diags.errors[0].attribute, path);
+    ///
+    /// let mut diags = Diagnostics::default();
+    /// assert_eq!(ValueString::Null.parse_into_diag::<u32>(&mut diags, path.clone(), 8), None);
+    /// assert_eq!(ValueString::Unknown.parse_into_diag::<u32>(&mut diags, path.clone(), 8), None);
+    /// assert!(!diags.has_errors());
+    /// ```
+    pub fn parse_into_diag<T: FromStrRadix>(
+        &self,
+        diags: &mut Diagnostics,
+        path: AttributePath,
+        radix: u32,
+    ) -> Option<T> {
+        let Self::Value(value) = self else {
+            return None;
+        };
+        match T::from_str_radix(value, radix) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                diags.error(
+                    "Invalid number",
+                    format!("`{value}` is not a valid base {radix} number: {err}"),
+                    path,
+                );
+                None
+            }
+        }
+    }
+
+    /// Parse the string as a boolean
+    ///
+    /// `true`, `yes`, and `1` are parsed as `true`, while `false`, `no`, and `0` are parsed as `false`.
+    /// Parsing is case-insensitive, and ignores leading and trailing whitespace (eg: the trailing newline of a command output).
+    ///
+    /// # Remarks
+    ///
+    /// Returns [`Value::Null`] if the string is not a recognized boolean.
+    /// Null and unknown values are passed through.
+    /// See [`Value::parse_bool_diag`] to report unrecognized booleans.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::value::{Value, ValueString};
+    /// for (text, expected) in [("true", true), ("Yes", true), ("1\n", true), ("FALSE", false), ("no", false), (" 0 ", false)] {
+    ///     assert_eq!(ValueString::from(text).parse_bool(), Value::Value(expected));
+    /// }
+    /// for text in ["", "2", "on", "truthy", "y"] {
+    ///     assert_eq!(ValueString::from(text).parse_bool(), Value::Null);
+    /// }
+    /// assert_eq!(ValueString::Null.parse_bool(), Value::Null);
+    /// assert_eq!(ValueString::Unknown.parse_bool(), Value::Unknown);
+    /// ```
+    pub fn parse_bool(&self) -> Value<bool> {
+        match self {
+            Self::Value(value) => match value.trim().to_ascii_lowercase().as_str() {
+                "true" | "yes" | "1" => Value::Value(true),
+                "false" | "no" | "0" => Value::Value(false),
+                _ => Value::Null,
+            },
+            Self::Null => Value::Null,
+            Self::Unknown => Value::Unknown,
+        }
+    }
+
+    /// Parse the string as a boolean, reporting unrecognized booleans in diagnostics
+    ///
+    /// # Arguments
+    ///
+    /// * `diags` - Diagnostics where unrecognized booleans are reported
+    /// * `path` - Path of the attribute reported in diagnostics
+    ///
+    /// # Remarks
+    ///
+    /// Same as [`Value::parse_bool`], but an error is reported on `path` if the string is not a recognized boolean.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tf_provider::value::{Value, ValueString};
+    /// # use tf_provider::{AttributePath, Diagnostics};
+    /// let mut diags = Diagnostics::default();
+    /// let path = AttributePath::new("enabled");
+    ///
+    /// let enabled: ValueString = "Yes".into();
+    /// assert_eq!(enabled.parse_bool_diag(&mut diags, path.clone()), Value::Value(true));
+    /// assert_eq!(ValueString::Unknown.parse_bool_diag(&mut diags, path.clone()), Value::Unknown);
+    /// assert!(!diags.has_errors());
+    ///
+    /// let enabled: ValueString = "enabled".into();
+    /// assert_eq!(enabled.parse_bool_diag(&mut diags, path.clone()), Value::Null);
+    /// assert_eq!(diags.error_count(), 1);
+    /// assert_eq!(diags.errors[0].attribute, path);
+    /// ```
+    pub fn parse_bool_diag(&self, diags: &mut Diagnostics, path: AttributePath) -> Value<bool> {
+        let parsed = self.parse_bool();
+        if let (Self::Value(value), Value::Null) = (self, &parsed) {
+            diags.error(
+                "Invalid boolean",
+                format!("`{value}` is not a valid boolean: expected one of `true`, `false`, `yes`, `no`, `1`, or `0`"),
+                path,
+            );
+        }
+        parsed
+    }
+}
+
+/// Integers that can be parsed from a string in a given base
+///
+/// See [`Value::parse_into_diag`]
+pub trait FromStrRadix: Sized {
+    /// Parse an integer from a string in the given base
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - String to parse
+    /// * `radix` - Base of the number, from 2 to 36
+    fn from_str_radix(src: &str, radix: u32) -> Result<Self, std::num::ParseIntError>;
+}
+
+macro_rules! impl_from_str_radix {
+    ($($t:ty),*) => {
+        $(
+            impl FromStrRadix for $t {
+                #[inline]
+                fn from_str_radix(src: &str, radix: u32) -> Result<Self, std::num::ParseIntError> {
+                    <$t>::from_str_radix(src, radix)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_str_radix!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// Error returned by [`Value::decode_hex`]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum HexDecodeError {
+    /// The string has an odd number of bytes
+    OddLength,
+    /// The string contains a character that is not an hexadecimal digit, at the given byte offset
+    InvalidCharacter(usize, char),
+}
+
+impl Display for HexDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OddLength => f.write_str("Odd number of hexadecimal digits"),
+            Self::InvalidCharacter(index, c) => {
+                write!(f, "Invalid hexadecimal digit {c:?} at offset {index}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for HexDecodeError {}
+
+impl<'a, V> Value<BTreeMap<Cow<'a, str>, Value<V>>> {
+    /// Get a reference to the value stored at `key`
+    ///
+    /// If the key is absent, returns [`Value::Null`].
+    /// If the map is [`Value::Null`], returns [`Value::Null`].
+    /// If the map is [`Value::Unknown`], returns [`Value::Unknown`].
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - key of the value to get
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::BTreeMap;
+    /// # use tf_provider::value::{Value, ValueMap, ValueString};
+    /// let map: ValueMap<ValueString> = Value::Value(BTreeMap::from([
+    ///     ("present".into(), "value".into()),
+    ///     ("null".into(), Value::Null),
+    ///     ("unknown".into(), Value::Unknown),
+    /// ]));
+    /// assert_eq!(map.get_value("present").as_str(), "value");
+    /// assert_eq!(map.get_value("null"), Value::Null);
+    /// assert_eq!(map.get_value("unknown"), Value::Unknown);
+    /// assert_eq!(map.get_value("absent"), Value::Null);
+    ///
+    /// let map: ValueMap<ValueString> = Value::Unknown;
+    /// assert_eq!(map.get_value("absent"), Value::Unknown);
+    /// ```
+    pub fn get_value<Q>(&self, key: &Q) -> Value<&V>
+    where
+        Cow<'a, str>: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match self {
+            Self::Value(map) => map.get(key).map_or(Value::Null, Value::as_ref),
+            Self::Null => Value::Null,
+            Self::Unknown => Value::Unknown,
+        }
+    }
+}
+
+impl<'a, T> Value<BTreeMap<Cow<'a, str>, T>> {
+    /// Get the entry of the map at `key` for in-place manipulation
+    ///
+    /// If the map is [`Value::Null`] or [`Value::Unknown`], it is first replaced by an empty map.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - key of the entry to get
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::BTreeMap;
+    /// # use tf_provider::value::{Value, ValueMap, ValueString};
+    /// let mut map: ValueMap<ValueString> = Value::Null;
+    /// map.entry("null").or_insert(Value::Null);
+    /// assert_eq!(map, Value::Value(BTreeMap::from([("null".into(), Value::Null)])));
+    ///
+    /// let mut map: ValueMap<ValueString> = Value::Unknown;
+    /// *map.entry("key").or_default() = "value".into();
+    /// assert_eq!(map.get_value("key").as_str(), "value");
+    ///
+    /// map.entry("key").and_modify(|value| *value = Value::Unknown);
+    /// map.entry("other").or_insert("other".into());
+    /// assert_eq!(map.get_value("key"), Value::Unknown);
+    /// assert_eq!(map.get_value("other").as_str(), "other");
+    /// ```
+    pub fn entry<K>(&mut self, key: K) -> std::collections::btree_map::Entry<'_, Cow<'a, str>, T>
+    where
+        K: Into<Cow<'a, str>>,
+    {
+        if !self.is_value() {
+            *self = Self::Value(BTreeMap::new());
+        }
+        let Self::Value(map) = self else {
+            unreachable!("the map has just been set")
+        };
+        map.entry(key.into())
+    }
 }
+
 impl<'a> Value<&'a Cow<'a, str>> {
     /// Maps a `&Value<&'a Cow<'a, str>>` to `&'a str`
     ///
@@ -1378,10 +2987,11 @@ impl Debug for ValueAny {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::String(value) => Debug::fmt(value, f),
-            Self::Number(value) => Debug::fmt(value, f),
+            Self::Number(value) => Display::fmt(value, f),
             Self::Bool(value) => Debug::fmt(value, f),
             Self::List(value) => Debug::fmt(value, f),
             Self::Map(value) => Debug::fmt(value, f),
+            Self::Dynamic(typ, value) => write!(f, "Dynamic({typ}, {value:?})"),
             Self::Null => f.write_str("Null"),
             Self::Unknown => f.write_str("Unknown"),
         }
@@ -0,0 +1,116 @@
+// This file is part of the tf-provider project
+//
+// Copyright (C) ANEO, 2024-2024. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Structural comparison of [`ValueAny`]
+
+use crate::attribute_path::AttributePath;
+use crate::value::ValueAny;
+
+/// Get the paths of the values that differ between `prior` and `proposed`
+///
+/// Maps are compared key by key, a missing key being equivalent to a null value.
+/// Lists are compared index-wise: a reordered list differs at every moved index,
+/// and the extra elements of the longest list differ.
+/// Any other pair of values differs at the path itself, without descending into it.
+///
+/// # Arguments
+///
+/// * `prior` - Value before the change
+/// * `proposed` - Value after the change
+///
+/// # Remarks
+///
+/// A null value differs from any non-null value, including an unknown value.
+/// Two unknown values are considered equal, as nothing is known to have changed.
+/// Map keys are reported as attribute steps, and the paths are sorted in traversal order.
+///
+/// # Examples
+///
+/// ```
+/// # use serde_json::json;
+/// # use tf_provider::value::ValueAny;
+/// # use tf_provider::value_diff::diff;
+/// # use tf_provider::AttributePath;
+/// let prior = ValueAny::try_from(json!({
+///     "command": "echo",
+///     "args": ["a", "b", "c"],
+///     "env": {"HOME": "/root", "USER": "root"},
+///     "connect": {"host": "localhost", "port": 22},
+/// })).unwrap();
+/// let proposed = ValueAny::try_from(json!({
+///     "command": "echo",
+///     "args": ["b", "a"],
+///     "env": {"HOME": "/root", "SHELL": "/bin/sh"},
+///     "connect": null,
+/// })).unwrap();
+///
+/// assert_eq!(diff(&prior, &proposed), [
+///     AttributePath::new("args").index(0),
+///     AttributePath::new("args").index(1),
+///     AttributePath::new("args").index(2),
+///     AttributePath::new("connect"),
+///     AttributePath::new("env").attribute("SHELL"),
+///     AttributePath::new("env").attribute("USER"),
+/// ]);
+///
+/// // Unknown values
+/// let ValueAny::Map(mut unknown) = prior.clone() else { unreachable!() };
+/// unknown.insert("command".to_owned(), ValueAny::Unknown);
+/// let unknown = ValueAny::Map(unknown);
+/// assert_eq!(diff(&prior, &unknown), [AttributePath::new("command")]);
+/// assert_eq!(diff(&unknown, &unknown), []);
+/// assert_eq!(diff(&ValueAny::Null, &ValueAny::Unknown), [AttributePath::root()]);
+/// ```
+pub fn diff(prior: &ValueAny, proposed: &ValueAny) -> Vec<AttributePath> {
+    let mut paths = Vec::new();
+    diff_into(&mut paths, prior, proposed, AttributePath::root());
+    paths
+}
+
+fn diff_into(
+    paths: &mut Vec<AttributePath>,
+    prior: &ValueAny,
+    proposed: &ValueAny,
+    path: AttributePath,
+) {
+    match (prior, proposed) {
+        (ValueAny::Map(prior), ValueAny::Map(proposed)) => {
+            let mut keys = prior.keys().chain(proposed.keys()).collect::<Vec<_>>();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                diff_into(
+                    paths,
+                    prior.get(key).unwrap_or(&ValueAny::Null),
+                    proposed.get(key).unwrap_or(&ValueAny::Null),
+                    path.clone().attribute(key.clone()),
+                );
+            }
+        }
+        (ValueAny::List(prior), ValueAny::List(proposed)) => {
+            for i in 0..prior.len().max(proposed.len()) {
+                match (prior.get(i), proposed.get(i)) {
+                    (Some(prior), Some(proposed)) => {
+                        diff_into(paths, prior, proposed, path.clone().index(i as i64))
+                    }
+                    _ => paths.push(path.clone().index(i as i64)),
+                }
+            }
+        }
+        (prior, proposed) if prior == proposed => (),
+        _ => paths.push(path),
+    }
+}
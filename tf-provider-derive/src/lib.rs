@@ -0,0 +1,114 @@
+// This file is part of the tf-provider project
+//
+// Copyright (C) ANEO, 2024-2024. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License")
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Derive macros for the tf-provider library
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Index, Member};
+
+/// Derive a `normalize(&mut self)` method that turns the annotated null fields into unknown
+///
+/// Fields annotated with `#[normalize(unknown_if_null)]` must be `Value`s.
+/// This is typically used on the state of a resource to mark the computed fields as unknown during the plan.
+///
+/// # Examples
+///
+/// ```
+/// use tf_provider::value::{Value, ValueString};
+/// use tf_provider::Normalize;
+///
+/// #[derive(Normalize)]
+/// struct State<'a> {
+///     path: ValueString<'a>,
+///     mode: ValueString<'a>,
+///     #[normalize(unknown_if_null)]
+///     id: ValueString<'a>,
+///     #[normalize(unknown_if_null)]
+///     sha256: ValueString<'a>,
+///     #[normalize(unknown_if_null)]
+///     size: Value<i64>,
+/// }
+///
+/// let mut state = State {
+///     path: Value::from("/tmp/file"),
+///     mode: Value::Null,
+///     id: Value::Null,
+///     sha256: Value::from("e3b0c442"),
+///     size: Value::Unknown,
+/// };
+/// state.normalize();
+///
+/// assert_eq!(state.path, Value::from("/tmp/file"));
+/// assert_eq!(state.mode, Value::Null);
+/// assert_eq!(state.id, Value::Unknown);
+/// assert_eq!(state.sha256, Value::from("e3b0c442"));
+/// assert_eq!(state.size, Value::Unknown);
+/// ```
+#[proc_macro_derive(Normalize, attributes(normalize))]
+pub fn derive_normalize(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match normalize_impl(&input) {
+        Ok(output) => output.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn normalize_impl(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "`Normalize` can only be derived for structs",
+        ));
+    };
+
+    let mut statements = Vec::new();
+    for (index, field) in data.fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(index)),
+        };
+        for attr in &field.attrs {
+            if !attr.path().is_ident("normalize") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("unknown_if_null") {
+                    statements.push(quote! {
+                        if self.#member.is_null() {
+                            self.#member = ::tf_provider::value::Value::Unknown;
+                        }
+                    });
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported normalize attribute, expected `unknown_if_null`"))
+                }
+            })?;
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Turn the null fields annotated with `#[normalize(unknown_if_null)]` into unknown
+            pub fn normalize(&mut self) {
+                #(#statements)*
+            }
+        }
+    })
+}